@@ -0,0 +1,28 @@
+//! Counts day 1 window increments of depths read from stdin without keeping them in memory.
+//!
+//! Usage: cargo run --release --example day1_stream [window size] < depths
+
+use std::{env, io, process};
+
+use aoc2021::day1;
+
+fn main() {
+    let window_size = match env::args().nth(1) {
+        None => 1,
+        Some(arg) => match arg.parse() {
+            Ok(size) if size > 0 => size,
+            _ => {
+                eprintln!("error: invalid window size '{}'", arg);
+                process::exit(2);
+            }
+        },
+    };
+
+    match day1::calculate_increments_streaming(io::stdin().lock(), window_size) {
+        Ok(increments) => println!("{}", increments),
+        Err(e) => {
+            eprintln!("error: {}", e);
+            process::exit(1);
+        }
+    }
+}
@@ -0,0 +1,84 @@
+//! Compares the row based day 3 functions with the bit-sliced report on a generated report.
+//!
+//! Usage: cargo run --release --example day3_engines [rows] [width]
+
+use std::{env, hint::black_box, process};
+
+use aoc2021::bench::{self, BenchConfig, Stats};
+use aoc2021::day3::{self, bitslice::BitSlicedReport, TiePolicy};
+use aoc2021::report::format_duration;
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let rows = parse_arg(args.first(), 2_000_000);
+    let width = parse_arg(args.get(1), 32);
+    if !(1..=day3::MAX_WIDTH).contains(&width) {
+        eprintln!("error: width must be between 1 and {}", day3::MAX_WIDTH);
+        process::exit(2);
+    }
+
+    let bits = generate_report(rows, width);
+    let report = BitSlicedReport::from_rows(&bits, width);
+    let config = BenchConfig {
+        warmup: 1,
+        iterations: 10,
+    };
+    println!("{} rows of {} bits", rows, width);
+
+    let rows_stats = bench::measure(&config, || {
+        let bits = black_box(&bits);
+        black_box(day3::get_sequence_of_most_common_bits(bits, width, TiePolicy::PreferOne).ok());
+        black_box(day3::get_sequence_of_least_common_bits(bits, width, TiePolicy::PreferZero).ok());
+    });
+    let sliced_stats = bench::measure(&config, || {
+        let report = black_box(&report);
+        black_box(report.most_common_bits(TiePolicy::PreferOne).ok());
+        black_box(report.least_common_bits(TiePolicy::PreferZero).ok());
+    });
+    print_comparison("gamma and epsilon", &rows_stats, &sliced_stats);
+
+    let rows_stats = bench::measure(&config, || {
+        let bits = black_box(&bits);
+        black_box(day3::get_oxygen_rating(bits, width));
+        black_box(day3::get_co2_rating(bits, width));
+    });
+    let sliced_stats = bench::measure(&config, || {
+        let report = black_box(&report);
+        black_box(report.oxygen_rating());
+        black_box(report.co2_rating());
+    });
+    print_comparison("ratings", &rows_stats, &sliced_stats);
+}
+
+fn parse_arg<T: std::str::FromStr>(arg: Option<&String>, default: T) -> T {
+    match arg {
+        Some(arg) => arg.parse().unwrap_or_else(|_| {
+            eprintln!("error: '{}' is not a valid number", arg);
+            process::exit(2);
+        }),
+        None => default,
+    }
+}
+
+// xorshift, so every run times the same report
+fn generate_report(rows: usize, width: u32) -> Vec<u64> {
+    let mut state = 0x2545_f491_4f6c_dd1d_u64;
+    (0..rows)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state >> (u64::BITS - width)
+        })
+        .collect()
+}
+
+fn print_comparison(name: &str, rows: &Stats, sliced: &Stats) {
+    println!(
+        "{}: rows median {}, bit-sliced median {} ({:.1}x faster)",
+        name,
+        format_duration(rows.median),
+        format_duration(sliced.median),
+        rows.median.as_secs_f64() / sliced.median.as_secs_f64()
+    );
+}
@@ -0,0 +1,348 @@
+//! Repeated timing of solutions and comparison with a saved baseline, using std timing only.
+
+use std::{
+    fmt::Write as _,
+    fs,
+    io::{self, BufRead, BufReader},
+    path::Path,
+    time::{Duration, Instant},
+};
+
+use crate::parse::{numbered_lines, parse_number, tokens, ParseError};
+use crate::solution::Part;
+
+/// Number of untimed warm-up runs and timed runs per part
+#[derive(Debug, Clone, PartialEq)]
+pub struct BenchConfig {
+    pub warmup: usize,
+    pub iterations: usize,
+}
+
+impl Default for BenchConfig {
+    fn default() -> Self {
+        Self {
+            warmup: 3,
+            iterations: 25,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Stats {
+    pub iterations: usize,
+    pub mean: Duration,
+    pub median: Duration,
+    pub min: Duration,
+    pub stddev: Duration,
+}
+
+impl Stats {
+    pub fn from_samples(samples: &[Duration]) -> Self {
+        assert!(!samples.is_empty(), "No samples to compute statistics from");
+
+        let mut sorted = samples.to_vec();
+        sorted.sort();
+        let n = sorted.len();
+        let median = match n % 2 {
+            0 => (sorted[n / 2 - 1] + sorted[n / 2]) / 2,
+            _ => sorted[n / 2],
+        };
+
+        let mean = sorted.iter().map(|d| d.as_secs_f64()).sum::<f64>() / n as f64;
+        // Sample standard deviation, a single run has no spread
+        let variance = match n {
+            1 => 0.0,
+            _ => {
+                sorted
+                    .iter()
+                    .map(|d| (d.as_secs_f64() - mean).powi(2))
+                    .sum::<f64>()
+                    / (n - 1) as f64
+            }
+        };
+
+        Self {
+            iterations: n,
+            mean: Duration::from_secs_f64(mean),
+            median,
+            min: sorted[0],
+            stddev: Duration::from_secs_f64(variance.sqrt()),
+        }
+    }
+}
+
+/// Runs `f` for the configured warm-up and timed iterations
+pub fn measure(config: &BenchConfig, mut f: impl FnMut()) -> Stats {
+    for _ in 0..config.warmup {
+        f();
+    }
+    let samples = (0..config.iterations.max(1))
+        .map(|_| {
+            let start = Instant::now();
+            f();
+            start.elapsed()
+        })
+        .collect::<Vec<Duration>>();
+    Stats::from_samples(&samples)
+}
+
+/// Saved medians, one `<day> <part> <median in ns>` per line
+#[derive(Debug, Default, PartialEq)]
+pub struct Baseline {
+    entries: Vec<(u8, Part, Duration)>,
+}
+
+impl Baseline {
+    /// Reads a baseline file, a missing file is an empty baseline
+    pub fn read(path: &Path) -> Result<Self, ParseError> {
+        match fs::File::open(path) {
+            Ok(file) => Self::parse(BufReader::new(file)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(io::Error::new(e.kind(), format!("{}: {}", path.display(), e)).into()),
+        }
+    }
+
+    pub fn parse(reader: impl BufRead) -> Result<Self, ParseError> {
+        let mut baseline = Self::default();
+        for line in numbered_lines(reader) {
+            let (line_number, line) = line?;
+            if line.trim().starts_with('#') {
+                continue;
+            }
+            let values = tokens(&line, char::is_whitespace).collect::<Vec<_>>();
+            match values[..] {
+                [] => continue,
+                [(c0, day), (c1, part), (c2, median)] => {
+                    let part = match part {
+                        "1" => Part::One,
+                        "2" => Part::Two,
+                        _ => {
+                            return Err(ParseError::UnexpectedToken {
+                                line: line_number,
+                                column: c1,
+                                token: part.to_string(),
+                                expected: "part 1 or 2",
+                            })
+                        }
+                    };
+                    baseline.set(
+                        parse_number(day, line_number, c0)?,
+                        part,
+                        Duration::from_nanos(parse_number(median, line_number, c2)?),
+                    );
+                }
+                _ => {
+                    return Err(ParseError::WrongLength {
+                        line: line_number,
+                        item: "values",
+                        expected: 3,
+                        found: values.len(),
+                    })
+                }
+            }
+        }
+        Ok(baseline)
+    }
+
+    pub fn get(&self, day: u8, part: Part) -> Option<Duration> {
+        self.entries
+            .iter()
+            .find(|(d, p, _)| *d == day && *p == part)
+            .map(|(_, _, median)| *median)
+    }
+
+    pub fn set(&mut self, day: u8, part: Part, median: Duration) {
+        match self
+            .entries
+            .iter_mut()
+            .find(|(d, p, _)| *d == day && *p == part)
+        {
+            Some(entry) => entry.2 = median,
+            None => self.entries.push((day, part, median)),
+        }
+        self.entries.sort_by_key(|(d, p, _)| (*d, *p == Part::Two));
+    }
+
+    /// Sets the medians of `results` for `day`, entries of other days and parts are kept
+    pub fn merge(&mut self, day: u8, results: &[(Part, Stats)]) {
+        for (part, stats) in results.iter() {
+            self.set(day, *part, stats.median);
+        }
+    }
+
+    pub fn write(&self, path: &Path) -> io::Result<()> {
+        let mut contents = String::from("# day part median_ns\n");
+        for (day, part, median) in self.entries.iter() {
+            writeln!(contents, "{} {} {}", day, part, median.as_nanos()).unwrap();
+        }
+        fs::write(path, contents)
+    }
+}
+
+/// How the median of one part compares to its saved median
+#[derive(Debug, Clone, PartialEq)]
+pub struct Comparison {
+    pub part: Part,
+    pub baseline: Duration,
+    /// Change in percent, None if the baseline is 0 and has no relative change
+    pub change: Option<f64>,
+    /// Whether the change is more than the allowed threshold
+    pub regressed: bool,
+}
+
+/// Compares every part of `results` that has a baseline, in the order of `results`
+pub fn compare(
+    day: u8,
+    results: &[(Part, Stats)],
+    baseline: &Baseline,
+    threshold: f64,
+) -> Vec<Comparison> {
+    results
+        .iter()
+        .filter_map(|(part, stats)| {
+            let previous = baseline.get(day, *part)?;
+            let change = change_percent(stats, previous);
+            Some(Comparison {
+                part: *part,
+                baseline: previous,
+                change,
+                regressed: change.is_some_and(|change| change > threshold),
+            })
+        })
+        .collect()
+}
+
+/// Relative change of the median compared to the baseline, in percent, None for a baseline
+/// of 0
+pub fn change_percent(stats: &Stats, baseline: Duration) -> Option<f64> {
+    match baseline.is_zero() {
+        true => None,
+        false => Some((stats.median.as_secs_f64() / baseline.as_secs_f64() - 1.0) * 100.0),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stats_with_median(median: Duration) -> Stats {
+        Stats::from_samples(&[median])
+    }
+
+    #[test]
+    fn test_stats_from_samples() {
+        let samples = [4, 2, 8, 6].map(Duration::from_micros);
+        let stats = Stats::from_samples(&samples);
+        assert_eq!(stats.iterations, 4);
+        assert_eq!(stats.mean, Duration::from_micros(5));
+        assert_eq!(stats.median, Duration::from_micros(5));
+        assert_eq!(stats.min, Duration::from_micros(2));
+        // sqrt(20 / 3) µs
+        assert_eq!(stats.stddev.as_nanos(), 2582);
+    }
+
+    #[test]
+    fn test_stats_from_single_sample() {
+        let stats = stats_with_median(Duration::from_millis(3));
+        assert_eq!(stats.median, Duration::from_millis(3));
+        assert_eq!(stats.stddev, Duration::ZERO);
+    }
+
+    #[test]
+    fn test_measure_runs_warmup_and_iterations() {
+        let mut runs = 0;
+        let config = BenchConfig {
+            warmup: 2,
+            iterations: 5,
+        };
+        let stats = measure(&config, || runs += 1);
+        assert_eq!(runs, 7);
+        assert_eq!(stats.iterations, 5);
+    }
+
+    #[test]
+    fn test_parse_baseline() {
+        let baseline =
+            Baseline::parse("# day part median_ns\n7 2 1500\n\n7 1 250\n".as_bytes()).unwrap();
+        assert_eq!(baseline.get(7, Part::One), Some(Duration::from_nanos(250)));
+        assert_eq!(baseline.get(7, Part::Two), Some(Duration::from_nanos(1500)));
+        assert_eq!(baseline.get(6, Part::One), None);
+
+        assert_eq!(
+            Baseline::parse("7 3 10\n".as_bytes())
+                .unwrap_err()
+                .to_string(),
+            "line 1, column 3: expected part 1 or 2, found '3'"
+        );
+        assert_eq!(
+            Baseline::parse("7 1\n".as_bytes()).unwrap_err().to_string(),
+            "line 1: expected 3 values, found 2"
+        );
+    }
+
+    #[test]
+    fn test_baseline_set_replaces_entry() {
+        let mut baseline = Baseline::default();
+        baseline.set(7, Part::Two, Duration::from_nanos(10));
+        baseline.set(7, Part::One, Duration::from_nanos(20));
+        baseline.set(7, Part::Two, Duration::from_nanos(30));
+        assert_eq!(
+            baseline.entries,
+            vec![
+                (7, Part::One, Duration::from_nanos(20)),
+                (7, Part::Two, Duration::from_nanos(30))
+            ]
+        );
+    }
+
+    #[test]
+    fn test_baseline_merge_keeps_other_entries() {
+        let mut baseline = Baseline::parse("6 1 100\n7 1 200\n7 2 300\n".as_bytes()).unwrap();
+        let results = vec![(Part::Two, stats_with_median(Duration::from_nanos(400)))];
+        baseline.merge(7, &results);
+        assert_eq!(
+            baseline.entries,
+            vec![
+                (6, Part::One, Duration::from_nanos(100)),
+                (7, Part::One, Duration::from_nanos(200)),
+                (7, Part::Two, Duration::from_nanos(400))
+            ]
+        );
+        baseline.merge(8, &results);
+        assert_eq!(baseline.get(8, Part::Two), Some(Duration::from_nanos(400)));
+    }
+
+    #[test]
+    fn test_change_percent() {
+        let stats = stats_with_median(Duration::from_micros(110));
+        let change = change_percent(&stats, Duration::from_micros(100)).unwrap();
+        assert!((change - 10.0).abs() < 1e-9);
+        assert_eq!(change_percent(&stats, Duration::ZERO), None);
+    }
+
+    #[test]
+    fn test_compare() {
+        let baseline = Baseline::parse("7 1 100\n7 2 0\n".as_bytes()).unwrap();
+        let results = vec![
+            (Part::One, stats_with_median(Duration::from_nanos(150))),
+            (Part::Two, stats_with_median(Duration::from_nanos(150))),
+        ];
+        let comparisons = compare(7, &results, &baseline, 20.0);
+        assert_eq!(comparisons.len(), 2);
+        assert_eq!(comparisons[0].part, Part::One);
+        assert!((comparisons[0].change.unwrap() - 50.0).abs() < 1e-9);
+        assert!(comparisons[0].regressed);
+        // A saved median of 0 has no relative change to regress by
+        assert_eq!(
+            comparisons[1],
+            Comparison {
+                part: Part::Two,
+                baseline: Duration::ZERO,
+                change: None,
+                regressed: false
+            }
+        );
+        assert!(!compare(7, &results, &baseline, 60.0)[0].regressed);
+        assert!(compare(6, &results, &baseline, 20.0).is_empty());
+    }
+}
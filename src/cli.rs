@@ -0,0 +1,458 @@
+use std::{fmt, path::PathBuf};
+
+use aoc2021::bench::BenchConfig;
+use aoc2021::input::{InputSource, INPUT_DIR_VARIABLE};
+use aoc2021::report::Format;
+use aoc2021::solution::Part;
+
+pub fn usage() -> String {
+    format!(
+        "\
+Usage: aoc2021 <command> [options]
+
+Commands:
+    run <day> [--part 1|2] [--input <path>] [--format text|json]
+                            Solve one day, both parts unless --part is given
+    all [--format text|json]
+                            Solve every registered day and time them
+    verify [<day>]          Compare answers with the expected answers in
+                            <dir>/day<N>/answers, for one or all days
+    bench <day> [1|2] [--warmup <n>] [--iterations <n>] [--input <path>]
+          [--baseline <path>] [--save <path>] [--threshold <percent>]
+                            Time one day over many runs after a warm-up, and
+                            flag parts whose median got slower than the
+                            baseline by more than the threshold (default 10)
+    list                    List the registered days
+    help                    Show this message
+
+A bare day number, e.g. `aoc2021 4`, is the same as `aoc2021 run 4`.
+
+Inputs are read from <dir>/day<N>/input, where <dir> is taken from {} and
+defaults to src. Use --input to read a single file instead, or `--input -`
+to read from stdin.
+
+With --format json one JSON object is printed per line, for each day and part.
+
+--save writes the measured medians to a baseline file, keeping the entries of
+other days and parts already in it.",
+        INPUT_DIR_VARIABLE
+    )
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Command {
+    Run {
+        day: u8,
+        part: Option<Part>,
+        input: Option<InputSource>,
+        format: Format,
+    },
+    All {
+        format: Format,
+    },
+    Verify {
+        day: Option<u8>,
+    },
+    Bench {
+        day: u8,
+        part: Option<Part>,
+        input: Option<InputSource>,
+        config: BenchConfig,
+        baseline: Option<PathBuf>,
+        save: Option<PathBuf>,
+        threshold: f64,
+    },
+    List,
+    Help,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum UsageError {
+    MissingCommand,
+    UnknownCommand(String),
+    MissingDay,
+    InvalidDay(String),
+    MissingValue(&'static str),
+    InvalidPart(String),
+    InvalidFormat(String),
+    InvalidNumber(&'static str, String),
+    UnexpectedArgument(String),
+}
+
+/// Slowdown of the median, in percent, above which a part is reported as a regression
+pub const DEFAULT_THRESHOLD: f64 = 10.0;
+
+impl fmt::Display for UsageError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            UsageError::MissingCommand => write!(f, "no command given"),
+            UsageError::UnknownCommand(c) => write!(f, "unknown command '{}'", c),
+            UsageError::MissingDay => write!(f, "missing day number"),
+            UsageError::InvalidDay(d) => write!(f, "'{}' is not a valid day number", d),
+            UsageError::MissingValue(flag) => write!(f, "{} expects a value", flag),
+            UsageError::InvalidPart(p) => write!(f, "part must be 1 or 2, got '{}'", p),
+            UsageError::InvalidFormat(s) => {
+                write!(f, "format must be text or json, got '{}'", s)
+            }
+            UsageError::InvalidNumber(flag, v) => {
+                write!(f, "{} expects a number, got '{}'", flag, v)
+            }
+            UsageError::UnexpectedArgument(a) => write!(f, "unexpected argument '{}'", a),
+        }
+    }
+}
+
+// Arguments are expected without the program name
+pub fn parse_args(args: &[String]) -> Result<Command, UsageError> {
+    let (command, rest) = args.split_first().ok_or(UsageError::MissingCommand)?;
+    match command.as_str() {
+        "run" => parse_run(rest),
+        "all" => parse_all(rest),
+        "verify" => parse_verify(rest),
+        "bench" => parse_bench(rest),
+        "list" => no_more_arguments(rest).map(|_| Command::List),
+        "help" | "-h" | "--help" => Ok(Command::Help),
+        c if c.chars().all(|c| c.is_ascii_digit()) => parse_run(args),
+        c => Err(UsageError::UnknownCommand(c.to_string())),
+    }
+}
+
+fn parse_run(args: &[String]) -> Result<Command, UsageError> {
+    let (day, mut rest) = args.split_first().ok_or(UsageError::MissingDay)?;
+    let day = parse_day(day)?;
+
+    let mut part = None;
+    let mut input = None;
+    let mut format = Format::Text;
+    while let Some((arg, tail)) = rest.split_first() {
+        match arg.as_str() {
+            "--part" | "-p" => {
+                let (value, tail) = tail
+                    .split_first()
+                    .ok_or(UsageError::MissingValue("--part"))?;
+                part = Some(parse_part(value)?);
+                rest = tail;
+            }
+            "--input" | "-i" => {
+                let (value, tail) = tail
+                    .split_first()
+                    .ok_or(UsageError::MissingValue("--input"))?;
+                input = Some(InputSource::from_arg(value));
+                rest = tail;
+            }
+            "--format" | "-f" => {
+                let (value, tail) = tail
+                    .split_first()
+                    .ok_or(UsageError::MissingValue("--format"))?;
+                format = parse_format(value)?;
+                rest = tail;
+            }
+            a => return Err(UsageError::UnexpectedArgument(a.to_string())),
+        }
+    }
+    Ok(Command::Run {
+        day,
+        part,
+        input,
+        format,
+    })
+}
+
+fn parse_all(mut args: &[String]) -> Result<Command, UsageError> {
+    let mut format = Format::Text;
+    while let Some((arg, tail)) = args.split_first() {
+        match arg.as_str() {
+            "--format" | "-f" => {
+                let (value, tail) = tail
+                    .split_first()
+                    .ok_or(UsageError::MissingValue("--format"))?;
+                format = parse_format(value)?;
+                args = tail;
+            }
+            a => return Err(UsageError::UnexpectedArgument(a.to_string())),
+        }
+    }
+    Ok(Command::All { format })
+}
+
+fn parse_verify(args: &[String]) -> Result<Command, UsageError> {
+    match args.split_first() {
+        Some((day, rest)) => {
+            let day = parse_day(day)?;
+            no_more_arguments(rest)?;
+            Ok(Command::Verify { day: Some(day) })
+        }
+        None => Ok(Command::Verify { day: None }),
+    }
+}
+
+fn parse_bench(args: &[String]) -> Result<Command, UsageError> {
+    let (day, mut rest) = args.split_first().ok_or(UsageError::MissingDay)?;
+    let day = parse_day(day)?;
+
+    let mut part = None;
+    let mut input = None;
+    let mut config = BenchConfig::default();
+    let mut baseline = None;
+    let mut save = None;
+    let mut threshold = DEFAULT_THRESHOLD;
+    if let Some((value, tail)) = rest.split_first() {
+        if !value.starts_with('-') {
+            part = Some(parse_part(value)?);
+            rest = tail;
+        }
+    }
+    while let Some((arg, tail)) = rest.split_first() {
+        let flag = match arg.as_str() {
+            "--warmup" => "--warmup",
+            "--iterations" | "-n" => "--iterations",
+            "--input" | "-i" => "--input",
+            "--baseline" => "--baseline",
+            "--save" => "--save",
+            "--threshold" => "--threshold",
+            a => return Err(UsageError::UnexpectedArgument(a.to_string())),
+        };
+        let (value, tail) = tail.split_first().ok_or(UsageError::MissingValue(flag))?;
+        match flag {
+            "--warmup" => config.warmup = parse_flag_number(flag, value)?,
+            "--iterations" => config.iterations = parse_flag_number(flag, value)?,
+            "--input" => input = Some(InputSource::from_arg(value)),
+            "--baseline" => baseline = Some(PathBuf::from(value)),
+            "--save" => save = Some(PathBuf::from(value)),
+            _ => threshold = parse_flag_number(flag, value)?,
+        }
+        rest = tail;
+    }
+    if config.iterations == 0 {
+        return Err(UsageError::InvalidNumber("--iterations", "0".to_string()));
+    }
+    Ok(Command::Bench {
+        day,
+        part,
+        input,
+        config,
+        baseline,
+        save,
+        threshold,
+    })
+}
+
+fn parse_flag_number<T: std::str::FromStr>(flag: &'static str, s: &str) -> Result<T, UsageError> {
+    s.parse::<T>()
+        .map_err(|_| UsageError::InvalidNumber(flag, s.to_string()))
+}
+
+fn parse_day(s: &str) -> Result<u8, UsageError> {
+    s.parse::<u8>()
+        .map_err(|_| UsageError::InvalidDay(s.to_string()))
+}
+
+fn parse_part(s: &str) -> Result<Part, UsageError> {
+    match s {
+        "1" => Ok(Part::One),
+        "2" => Ok(Part::Two),
+        _ => Err(UsageError::InvalidPart(s.to_string())),
+    }
+}
+
+fn parse_format(s: &str) -> Result<Format, UsageError> {
+    match s {
+        "text" => Ok(Format::Text),
+        "json" => Ok(Format::Json),
+        _ => Err(UsageError::InvalidFormat(s.to_string())),
+    }
+}
+
+fn no_more_arguments(args: &[String]) -> Result<(), UsageError> {
+    match args.first() {
+        Some(a) => Err(UsageError::UnexpectedArgument(a.to_string())),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(s: &str) -> Vec<String> {
+        s.split_whitespace().map(String::from).collect()
+    }
+
+    #[test]
+    fn test_parse_run() {
+        assert_eq!(
+            parse_args(&args("run 4")),
+            Ok(Command::Run {
+                day: 4,
+                part: None,
+                input: None,
+                format: Format::Text
+            })
+        );
+        assert_eq!(
+            parse_args(&args("run 7 --part 2")),
+            Ok(Command::Run {
+                day: 7,
+                part: Some(Part::Two),
+                input: None,
+                format: Format::Text
+            })
+        );
+        assert_eq!(
+            parse_args(&args("3 -p 1")),
+            Ok(Command::Run {
+                day: 3,
+                part: Some(Part::One),
+                input: None,
+                format: Format::Text
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_run_with_input() {
+        assert_eq!(
+            parse_args(&args("run 5 --input -")),
+            Ok(Command::Run {
+                day: 5,
+                part: None,
+                input: Some(InputSource::Stdin),
+                format: Format::Text
+            })
+        );
+        assert_eq!(
+            parse_args(&args("run 5 -i other/day5.txt --part 1")),
+            Ok(Command::Run {
+                day: 5,
+                part: Some(Part::One),
+                input: Some(InputSource::from_arg("other/day5.txt")),
+                format: Format::Text
+            })
+        );
+        assert_eq!(
+            parse_args(&args("run 5 --input")),
+            Err(UsageError::MissingValue("--input"))
+        );
+    }
+
+    #[test]
+    fn test_parse_format() {
+        assert_eq!(
+            parse_args(&args("run 2 --format json")),
+            Ok(Command::Run {
+                day: 2,
+                part: None,
+                input: None,
+                format: Format::Json
+            })
+        );
+        assert_eq!(
+            parse_args(&args("all -f json")),
+            Ok(Command::All {
+                format: Format::Json
+            })
+        );
+        assert_eq!(
+            parse_args(&args("all --format yaml")),
+            Err(UsageError::InvalidFormat("yaml".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_parse_simple_commands() {
+        assert_eq!(
+            parse_args(&args("all")),
+            Ok(Command::All {
+                format: Format::Text
+            })
+        );
+        assert_eq!(parse_args(&args("list")), Ok(Command::List));
+        assert_eq!(
+            parse_args(&args("verify")),
+            Ok(Command::Verify { day: None })
+        );
+        assert_eq!(
+            parse_args(&args("verify 6")),
+            Ok(Command::Verify { day: Some(6) })
+        );
+        assert_eq!(parse_args(&args("--help")), Ok(Command::Help));
+    }
+
+    #[test]
+    fn test_parse_bench() {
+        assert_eq!(
+            parse_args(&args("bench 7")),
+            Ok(Command::Bench {
+                day: 7,
+                part: None,
+                input: None,
+                config: BenchConfig::default(),
+                baseline: None,
+                save: None,
+                threshold: DEFAULT_THRESHOLD
+            })
+        );
+        assert_eq!(
+            parse_args(&args(
+                "bench 7 2 --warmup 1 -n 50 --baseline bench.txt --save new.txt --threshold 5"
+            )),
+            Ok(Command::Bench {
+                day: 7,
+                part: Some(Part::Two),
+                input: None,
+                config: BenchConfig {
+                    warmup: 1,
+                    iterations: 50
+                },
+                baseline: Some(PathBuf::from("bench.txt")),
+                save: Some(PathBuf::from("new.txt")),
+                threshold: 5.0
+            })
+        );
+        assert_eq!(
+            parse_args(&args("bench 7 3")),
+            Err(UsageError::InvalidPart("3".to_string()))
+        );
+        assert_eq!(
+            parse_args(&args("bench 7 --iterations many")),
+            Err(UsageError::InvalidNumber(
+                "--iterations",
+                "many".to_string()
+            ))
+        );
+        assert_eq!(
+            parse_args(&args("bench 7 -n 0")),
+            Err(UsageError::InvalidNumber("--iterations", "0".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_parse_usage_errors() {
+        assert_eq!(parse_args(&[]), Err(UsageError::MissingCommand));
+        assert_eq!(
+            parse_args(&args("solve")),
+            Err(UsageError::UnknownCommand("solve".to_string()))
+        );
+        assert_eq!(parse_args(&args("run")), Err(UsageError::MissingDay));
+        assert_eq!(
+            parse_args(&args("run four")),
+            Err(UsageError::InvalidDay("four".to_string()))
+        );
+        assert_eq!(
+            parse_args(&args("run 4 --part")),
+            Err(UsageError::MissingValue("--part"))
+        );
+        assert_eq!(
+            parse_args(&args("run 4 --part 3")),
+            Err(UsageError::InvalidPart("3".to_string()))
+        );
+        assert_eq!(
+            parse_args(&args("verify 6 7")),
+            Err(UsageError::UnexpectedArgument("7".to_string()))
+        );
+        assert_eq!(
+            parse_args(&args("all 4")),
+            Err(UsageError::UnexpectedArgument("4".to_string()))
+        );
+    }
+}
@@ -0,0 +1,271 @@
+//! A report on how a depth series changes, beyond the number of increases.
+
+use std::{cmp::Ordering, fmt};
+
+use super::window::{Window, Windows};
+
+/// What a window of depths is reduced to before windows are compared
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Aggregate {
+    Sum,
+    Mean,
+    Max,
+}
+
+impl Aggregate {
+    // Windows are compared by this exact value, means of windows of one size compare like
+    // their sums
+    fn key(self, window: &Window) -> i128 {
+        match self {
+            Aggregate::Sum | Aggregate::Mean => window.sum,
+            Aggregate::Max => window.max.into(),
+        }
+    }
+
+    /// The aggregate of a window of `size` depths
+    pub fn value(self, window: &Window, size: usize) -> f64 {
+        self.key_value(self.key(window), size)
+    }
+
+    fn key_value(self, key: i128, size: usize) -> f64 {
+        match self {
+            Aggregate::Mean => key as f64 / size as f64,
+            Aggregate::Sum | Aggregate::Max => key as f64,
+        }
+    }
+}
+
+impl fmt::Display for Aggregate {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Aggregate::Sum => write!(f, "sum"),
+            Aggregate::Mean => write!(f, "mean"),
+            Aggregate::Max => write!(f, "max"),
+        }
+    }
+}
+
+/// A change between two neighbouring windows
+#[derive(Debug, Clone, PartialEq)]
+pub struct Jump {
+    /// Index of the window after the change, the first window being 0
+    pub window: usize,
+    pub from: f64,
+    pub to: f64,
+}
+
+impl Jump {
+    pub fn change(&self) -> f64 {
+        self.to - self.from
+    }
+}
+
+/// How the aggregates of sliding windows over a depth series change
+#[derive(Debug, Clone, PartialEq)]
+pub struct SweepReport {
+    pub window_size: usize,
+    pub aggregate: Aggregate,
+    /// Number of windows, one more than the number of comparisons
+    pub windows: usize,
+    pub increases: usize,
+    pub decreases: usize,
+    pub plateaus: usize,
+    /// Most increases in a row
+    pub longest_increasing_run: usize,
+    /// The change furthest from zero, the first one if several are as large
+    pub largest_jump: Option<Jump>,
+    pub lowest: Option<f64>,
+    pub highest: Option<f64>,
+}
+
+/// Analyses the windows of `window_size` depths, reading the depths once
+pub fn analyse_sweep(
+    depths: impl IntoIterator<Item = i64>,
+    window_size: usize,
+    aggregate: Aggregate,
+) -> SweepReport {
+    let mut report = SweepReport {
+        window_size,
+        aggregate,
+        windows: 0,
+        increases: 0,
+        decreases: 0,
+        plateaus: 0,
+        longest_increasing_run: 0,
+        largest_jump: None,
+        lowest: None,
+        highest: None,
+    };
+    let mut previous: Option<i128> = None;
+    let mut lowest = i128::MAX;
+    let mut highest = i128::MIN;
+    let mut largest_change = 0;
+    let mut run = 0;
+    for (idx, window) in Windows::new(depths, window_size).enumerate() {
+        let key = aggregate.key(&window);
+        report.windows += 1;
+        lowest = lowest.min(key);
+        highest = highest.max(key);
+        let Some(previous) = previous.replace(key) else {
+            continue;
+        };
+
+        match key.cmp(&previous) {
+            Ordering::Greater => {
+                report.increases += 1;
+                run += 1;
+                report.longest_increasing_run = report.longest_increasing_run.max(run);
+            }
+            Ordering::Less => {
+                report.decreases += 1;
+                run = 0;
+            }
+            Ordering::Equal => {
+                report.plateaus += 1;
+                run = 0;
+            }
+        }
+        let change = key.abs_diff(previous);
+        if report.largest_jump.is_none() || change > largest_change {
+            largest_change = change;
+            report.largest_jump = Some(Jump {
+                window: idx,
+                from: aggregate.key_value(previous, window_size),
+                to: aggregate.key_value(key, window_size),
+            });
+        }
+    }
+    if report.windows > 0 {
+        report.lowest = Some(aggregate.key_value(lowest, window_size));
+        report.highest = Some(aggregate.key_value(highest, window_size));
+    }
+    report
+}
+
+// Whole numbers without a fraction, means to two decimals
+fn format_value(value: f64) -> String {
+    match value.fract() == 0.0 {
+        true => format!("{}", value),
+        false => format!("{:.2}", value),
+    }
+}
+
+impl fmt::Display for SweepReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "windows of {} by {}: {}",
+            self.window_size, self.aggregate, self.windows
+        )?;
+        if let (Some(lowest), Some(highest)) = (self.lowest, self.highest) {
+            write!(
+                f,
+                ", lowest {}, highest {}",
+                format_value(lowest),
+                format_value(highest)
+            )?;
+        }
+        writeln!(f)?;
+        writeln!(
+            f,
+            "increases: {}, decreases: {}, plateaus: {}",
+            self.increases, self.decreases, self.plateaus
+        )?;
+        writeln!(f, "longest increasing run: {}", self.longest_increasing_run)?;
+        match &self.largest_jump {
+            Some(jump) => write!(
+                f,
+                "largest jump: {} to {} ({}{})",
+                format_value(jump.from),
+                format_value(jump.to),
+                if jump.change() >= 0.0 { "+" } else { "" },
+                format_value(jump.change())
+            ),
+            None => write!(f, "largest jump: none"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DEPTHS: [i64; 10] = [199, 200, 208, 210, 200, 207, 240, 269, 260, 263];
+
+    #[test]
+    fn test_analyse_single_depths() {
+        let report = analyse_sweep(DEPTHS, 1, Aggregate::Sum);
+        assert_eq!(report.windows, 10);
+        assert_eq!(report.increases, 7);
+        assert_eq!(report.decreases, 2);
+        assert_eq!(report.plateaus, 0);
+        assert_eq!(report.longest_increasing_run, 3);
+        assert_eq!(
+            report.largest_jump,
+            Some(Jump {
+                window: 6,
+                from: 207.0,
+                to: 240.0
+            })
+        );
+        assert_eq!(report.lowest, Some(199.0));
+        assert_eq!(report.highest, Some(269.0));
+    }
+
+    #[test]
+    fn test_analyse_window_sums() {
+        let report = analyse_sweep(DEPTHS, 3, Aggregate::Sum);
+        assert_eq!(report.windows, 8);
+        assert_eq!(
+            (report.increases, report.decreases, report.plateaus),
+            (5, 1, 1)
+        );
+        assert_eq!(report.longest_increasing_run, 4);
+        assert_eq!(report.largest_jump.unwrap().change(), 69.0);
+    }
+
+    #[test]
+    fn test_analyse_window_means_and_maxima() {
+        let means = analyse_sweep(DEPTHS, 3, Aggregate::Mean);
+        assert_eq!(means.increases, 5);
+        assert_eq!(means.largest_jump.unwrap().change(), 23.0);
+        assert_eq!(means.highest, Some(264.0));
+
+        let maxima = analyse_sweep(DEPTHS, 3, Aggregate::Max);
+        assert_eq!(
+            (maxima.increases, maxima.decreases, maxima.plateaus),
+            (3, 0, 4)
+        );
+        assert_eq!(maxima.longest_increasing_run, 2);
+        assert_eq!(maxima.largest_jump.unwrap().change(), 30.0);
+    }
+
+    #[test]
+    fn test_analyse_too_few_depths() {
+        let report = analyse_sweep([1, 2], 3, Aggregate::Sum);
+        assert_eq!(report.windows, 0);
+        assert_eq!(report.largest_jump, None);
+        assert_eq!(report.lowest, None);
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(
+            analyse_sweep(DEPTHS, 3, Aggregate::Sum).to_string(),
+            "windows of 3 by sum: 8, lowest 607, highest 792\n\
+             increases: 5, decreases: 1, plateaus: 1\n\
+             longest increasing run: 4\n\
+             largest jump: 647 to 716 (+69)"
+        );
+        assert_eq!(
+            analyse_sweep([3, 1], 1, Aggregate::Mean).to_string(),
+            "windows of 1 by mean: 2, lowest 1, highest 3\n\
+             increases: 0, decreases: 1, plateaus: 0\n\
+             longest increasing run: 0\n\
+             largest jump: 3 to 1 (-2)"
+        );
+        assert!(analyse_sweep(DEPTHS, 3, Aggregate::Mean)
+            .to_string()
+            .contains("lowest 202.33"));
+    }
+}
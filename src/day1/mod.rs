@@ -1,45 +1,75 @@
-use std::{fs, io::BufRead, io::BufReader};
-
-fn read_input() -> Vec<i32> {
-    let filename = "src/day1/input";
-    let file = fs::File::open(filename).unwrap();
-    let contents = BufReader::new(file);
-    contents
-        .lines()
-        .filter_map(|r| match r {
-            Ok(s) => s.parse::<i32>().ok(),
-            Err(_) => None,
-        })
-        .collect::<Vec<i32>>()
-}
+//! Day 1: Sonar Sweep
+
+pub mod analysis;
+pub mod window;
+
+use std::io::BufRead;
 
-fn calculate_increments(nums: &Vec<i32>, window_size: usize) -> i32 {
-    nums.windows(window_size + 1).fold(0, |acc, n| {
-        let first_window = &n[0..window_size];
-        let second_window = &n[1..window_size + 1];
+use crate::input::InputSource;
+use crate::parse::{numbered_lines, parse_number, single_token, ParseError};
+use crate::solution::{Answer, Solution};
+use window::count_increments;
 
-        let lhs: i32 = first_window.iter().sum();
-        let rhs: i32 = second_window.iter().sum();
+fn read_input(source: &InputSource) -> Result<Vec<i64>, ParseError> {
+    let depths = read_depths(source.open(1)?).collect::<Result<Vec<i64>, ParseError>>()?;
+    if depths.is_empty() {
+        return Err(ParseError::EmptyInput);
+    }
+    Ok(depths)
+}
 
-        match (rhs - lhs).signum() {
-            1 => acc + 1,
-            _ => acc,
-        }
+/// Depths one per line as they are read, blank lines skipped
+pub fn read_depths(reader: impl BufRead) -> impl Iterator<Item = Result<i64, ParseError>> {
+    numbered_lines(reader).filter_map(|line| {
+        let parse = || {
+            let (line_number, line) = line?;
+            match single_token(&line, line_number)? {
+                Some((column, token)) => parse_number(token, line_number, column).map(Some),
+                None => Ok(None),
+            }
+        };
+        parse().transpose()
     })
 }
 
-pub fn run() {
-    let depths = read_input();
+/// Number of times the sum of a sliding window increases from one window to the next
+pub fn calculate_increments(nums: &[i64], window_size: usize) -> usize {
+    count_increments(nums.iter().copied(), window_size)
+}
+
+/// Like [`calculate_increments`], reading the depths as they are needed so inputs of any
+/// length fit in memory
+pub fn calculate_increments_streaming(
+    reader: impl BufRead,
+    window_size: usize,
+) -> Result<usize, ParseError> {
+    let mut error = None;
+    let depths = read_depths(reader).map_while(|depth| depth.map_err(|e| error = Some(e)).ok());
+    let increments = count_increments(depths, window_size);
+    match error {
+        Some(e) => Err(e),
+        None => Ok(increments),
+    }
+}
+
+pub struct Day1;
+
+impl Solution for Day1 {
+    type Input = Vec<i64>;
 
-    println!(
-        "Part1: Number of depth increments: {}",
-        calculate_increments(&depths, 1)
-    );
+    fn parse(source: &InputSource) -> Result<Self::Input, ParseError> {
+        read_input(source)
+    }
+
+    // Number of depth increments
+    fn part1(depths: &Self::Input) -> Answer {
+        calculate_increments(depths, 1).into()
+    }
 
-    println!(
-        "Part2: Number of depth increments: {}",
-        calculate_increments(&depths, 3)
-    );
+    // Number of depth increments over a sliding window of three
+    fn part2(depths: &Self::Input) -> Answer {
+        calculate_increments(depths, 3).into()
+    }
 }
 
 #[cfg(test)]
@@ -57,4 +87,19 @@ mod tests {
         let depths = vec![199, 200, 208, 210, 200, 207, 240, 269, 260, 263];
         assert_eq!(calculate_increments(&depths, 3), 5);
     }
+
+    #[test]
+    fn test_calculate_increments_streaming() {
+        let depths = "199\n200\n208\n210\n\n200\n207\n240\n269\n260\n263\n";
+        assert_eq!(
+            calculate_increments_streaming(depths.as_bytes(), 3).unwrap(),
+            5
+        );
+        assert_eq!(
+            calculate_increments_streaming("199\n200\n2o8\n".as_bytes(), 1)
+                .unwrap_err()
+                .to_string(),
+            "line 3, column 1: '2o8' is not a valid number"
+        );
+    }
 }
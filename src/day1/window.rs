@@ -0,0 +1,210 @@
+//! Sliding window comparisons over a stream of depths, in memory bounded by the window size.
+//!
+//! Two neighbouring windows share all but one depth, so the later sum is larger exactly when
+//! the depth entering the window is larger than the one leaving it, and increments are counted
+//! without computing any sums. Where the windows themselves are needed, sums are updated by
+//! the same two depths instead of being summed again for every window, and the largest depth
+//! is kept at the front of a deque, so every window takes constant time on average whatever
+//! its size.
+
+use std::{cmp::Ordering, collections::VecDeque};
+
+/// How the sum changes from every window of `size` depths to the next
+pub struct WindowChanges<I> {
+    depths: I,
+    window: VecDeque<i64>,
+    size: usize,
+}
+
+impl<I: Iterator<Item = i64>> WindowChanges<I> {
+    pub fn new(depths: impl IntoIterator<IntoIter = I>, size: usize) -> Self {
+        assert!(size > 0, "Windows hold at least one depth");
+        Self {
+            depths: depths.into_iter(),
+            window: VecDeque::with_capacity(size),
+            size,
+        }
+    }
+}
+
+impl<I: Iterator<Item = i64>> Iterator for WindowChanges<I> {
+    type Item = Ordering;
+
+    fn next(&mut self) -> Option<Ordering> {
+        while self.window.len() < self.size {
+            self.window.push_back(self.depths.next()?);
+        }
+        let entering = self.depths.next()?;
+        let leaving = self.window.pop_front()?;
+        self.window.push_back(entering);
+        Some(entering.cmp(&leaving))
+    }
+}
+
+/// Number of times the sum of a sliding window of `size` depths increases
+pub fn count_increments(depths: impl IntoIterator<Item = i64>, size: usize) -> usize {
+    WindowChanges::new(depths, size)
+        .filter(|change| *change == Ordering::Greater)
+        .count()
+}
+
+/// Sum and largest depth of one window
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Window {
+    /// Wide enough that no window of `i64` depths can overflow it
+    pub sum: i128,
+    pub max: i64,
+}
+
+/// Every window of `size` depths, in order
+pub struct Windows<I> {
+    depths: I,
+    window: VecDeque<i64>,
+    // Index and depth of every depth in the window that no later depth is as large as, so
+    // the depths decrease and the first one is the largest of the window
+    maxima: VecDeque<(usize, i64)>,
+    size: usize,
+    // Number of depths read so far, the index of the next one
+    read: usize,
+    sum: i128,
+}
+
+impl<I: Iterator<Item = i64>> Windows<I> {
+    pub fn new(depths: impl IntoIterator<IntoIter = I>, size: usize) -> Self {
+        assert!(size > 0, "Windows hold at least one depth");
+        Self {
+            depths: depths.into_iter(),
+            window: VecDeque::with_capacity(size),
+            maxima: VecDeque::with_capacity(size),
+            size,
+            read: 0,
+            sum: 0,
+        }
+    }
+
+    fn push(&mut self, depth: i64) {
+        self.window.push_back(depth);
+        self.sum += i128::from(depth);
+        while self.maxima.back().is_some_and(|(_, max)| *max <= depth) {
+            self.maxima.pop_back();
+        }
+        self.maxima.push_back((self.read, depth));
+        self.read += 1;
+    }
+}
+
+impl<I: Iterator<Item = i64>> Iterator for Windows<I> {
+    type Item = Window;
+
+    fn next(&mut self) -> Option<Window> {
+        if self.window.len() == self.size {
+            let entering = self.depths.next()?;
+            let leaving = self.window.pop_front()?;
+            self.sum -= i128::from(leaving);
+            // The depth leaving is the oldest one, read `size` depths ago
+            if self
+                .maxima
+                .front()
+                .is_some_and(|(idx, _)| idx + self.size == self.read)
+            {
+                self.maxima.pop_front();
+            }
+            self.push(entering);
+        }
+        while self.window.len() < self.size {
+            let depth = self.depths.next()?;
+            self.push(depth);
+        }
+        let (_, max) = *self.maxima.front()?;
+        Some(Window { sum: self.sum, max })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::xorshift;
+
+    const DEPTHS: [i64; 10] = [199, 200, 208, 210, 200, 207, 240, 269, 260, 263];
+
+    #[test]
+    fn test_window_changes() {
+        use Ordering::*;
+        let changes = WindowChanges::new(DEPTHS, 3).collect::<Vec<_>>();
+        assert_eq!(
+            changes,
+            vec![Greater, Equal, Less, Greater, Greater, Greater, Greater]
+        );
+    }
+
+    #[test]
+    fn test_windows() {
+        let windows = Windows::new(DEPTHS, 3).collect::<Vec<_>>();
+        assert_eq!(windows.len(), 8);
+        assert_eq!(windows[0], Window { sum: 607, max: 208 });
+        assert_eq!(windows[3], Window { sum: 617, max: 210 });
+        assert_eq!(windows[7], Window { sum: 792, max: 269 });
+        assert_eq!(Windows::new(DEPTHS, 11).next(), None);
+    }
+
+    #[test]
+    fn test_window_maxima() {
+        // The largest depth leaves the window while a smaller one is still in it
+        let depths = [5, 1, 4, 2, 3, 0, 0, 7];
+        let maxima = Windows::new(depths, 3)
+            .map(|window| window.max)
+            .collect::<Vec<_>>();
+        assert_eq!(maxima, vec![5, 4, 4, 3, 3, 7]);
+        let maxima = Windows::new(depths, 1)
+            .map(|window| window.max)
+            .collect::<Vec<_>>();
+        assert_eq!(maxima, depths);
+    }
+
+    #[test]
+    fn test_windows_match_rescanning() {
+        let depths = xorshift(7)
+            .take(1000)
+            .map(|value| (value % 50) as i64)
+            .collect::<Vec<_>>();
+        for size in [1, 2, 5, 64] {
+            let expected = depths
+                .windows(size)
+                .map(|window| Window {
+                    sum: window.iter().map(|depth| i128::from(*depth)).sum(),
+                    max: *window.iter().max().unwrap(),
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(
+                Windows::new(depths.iter().copied(), size).collect::<Vec<_>>(),
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn test_windows_of_extreme_depths() {
+        let depths = [i64::MAX, i64::MAX, i64::MIN, i64::MAX];
+        let sums = Windows::new(depths, 2)
+            .map(|window| window.sum)
+            .collect::<Vec<_>>();
+        assert_eq!(sums, vec![2 * i64::MAX as i128, -1, -1]);
+        assert_eq!(count_increments(depths, 2), 0);
+        assert_eq!(count_increments(depths, 1), 1);
+    }
+
+    #[test]
+    fn test_count_increments() {
+        assert_eq!(count_increments(DEPTHS, 1), 7);
+        assert_eq!(count_increments(DEPTHS, 3), 5);
+        assert_eq!(count_increments(DEPTHS, 10), 0);
+        assert_eq!(count_increments(std::iter::empty(), 3), 0);
+    }
+
+    #[test]
+    fn test_count_increments_on_generated_input() {
+        // Depths that keep rising, never collected into memory
+        let depths = (0..).take(1_000_000);
+        assert_eq!(count_increments(depths, 3), 1_000_000 - 3);
+    }
+}
@@ -1,79 +1,135 @@
-use std::{fs, io::BufRead, io::BufReader};
+//! Day 2: Dive!
 
-#[derive(Debug, PartialEq)]
-enum Command {
+use crate::input::InputSource;
+use crate::overflow::Overflow;
+use crate::parse::{numbered_lines, parse_number, tokens, ParseError};
+use crate::solution::{Answer, Solution};
+
+/// A single step of the planned course
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Command {
     Forward(i32),
     Down(i32),
     Up(i32),
 }
 
-fn read_input() -> Vec<Command> {
-    let filename = "src/day2/input";
-    let file = fs::File::open(filename).unwrap();
-    let contents = BufReader::new(file);
+fn read_input(source: &InputSource) -> Result<Vec<Command>, ParseError> {
+    let contents = source.open(2)?;
 
     let mut parsed_input: Vec<Command> = Vec::new();
-    for l in contents.lines() {
-        if let Ok(result) = l {
-            match parse_line(&result) {
-                Some(cmd) => parsed_input.push(cmd),
-                _ => {}
-            }
+    for line in numbered_lines(contents) {
+        let (line_number, line) = line?;
+        if !line.trim().is_empty() {
+            parsed_input.push(parse_line(&line, line_number)?);
         }
     }
-    parsed_input
+    if parsed_input.is_empty() {
+        return Err(ParseError::EmptyInput);
+    }
+    Ok(parsed_input)
 }
 
-fn parse_line(input: &str) -> Option<Command> {
+fn parse_line(input: &str, line: usize) -> Result<Command, ParseError> {
     use Command::*;
-    let components = input.split(' ').collect::<Vec<&str>>();
-    let digit = components[1].parse::<i32>().ok()?;
-    match components[0] {
-        "forward" => Some(Forward(digit)),
-        "down" => Some(Down(digit)),
-        "up" => Some(Up(digit)),
-        _ => None,
+    let mut components = tokens(input, char::is_whitespace);
+    let (direction_column, direction) = components.next().ok_or(ParseError::MissingToken {
+        line,
+        column: 1,
+        expected: "command",
+    })?;
+    let digit = match components.next() {
+        Some((column, token)) => parse_number::<i32>(token, line, column)?,
+        None => {
+            return Err(ParseError::MissingToken {
+                line,
+                column: direction_column + direction.len(),
+                expected: "distance",
+            })
+        }
+    };
+    if let Some((column, token)) = components.next() {
+        return Err(ParseError::UnexpectedToken {
+            line,
+            column,
+            token: token.to_string(),
+            expected: "end of line",
+        });
+    }
+    match direction {
+        "forward" => Ok(Forward(digit)),
+        "down" => Ok(Down(digit)),
+        "up" => Ok(Up(digit)),
+        _ => Err(ParseError::UnexpectedToken {
+            line,
+            column: direction_column,
+            token: direction.to_string(),
+            expected: "forward, down or up",
+        }),
     }
 }
 
-fn calculate_destination(path: &[Command]) -> (i32, i32) {
+/// Distance travelled and final depth, where up and down change the depth directly
+pub fn calculate_destination(path: &[Command]) -> Result<(i64, i64), Overflow> {
     use Command::*;
     path.iter()
-        .fold((0, 0), |(distance, depth), cmd| match cmd {
-            Forward(d) => (distance + d, depth),
-            Down(d) => (distance, depth + d),
-            Up(d) => (distance, depth - d),
+        .try_fold((0_i64, 0_i64), |(distance, depth), cmd| match *cmd {
+            Forward(d) => Some((distance.checked_add(d.into())?, depth)),
+            Down(d) => Some((distance, depth.checked_add(d.into())?)),
+            Up(d) => Some((distance, depth.checked_sub(d.into())?)),
         })
+        .ok_or(Overflow("destination"))
 }
 
-fn calculate_destination_with_aim(path: &[Command]) -> (i32, i32) {
+/// Distance travelled and final depth, where up and down change the aim
+pub fn calculate_destination_with_aim(path: &[Command]) -> Result<(i64, i64), Overflow> {
     use Command::*;
     let pos = path
         .iter()
-        .fold((0, 0, 0), |(distance, depth, aim), cmd| match cmd {
-            Forward(x) => (distance + x, depth + aim * x, aim),
-            Down(x) => (distance, depth, aim + x),
-            Up(x) => (distance, depth, aim - x),
-        });
-    (pos.0, pos.1)
+        .try_fold(
+            (0_i64, 0_i64, 0_i64),
+            |(distance, depth, aim), cmd| match *cmd {
+                Forward(x) => Some((
+                    distance.checked_add(x.into())?,
+                    depth.checked_add(aim.checked_mul(x.into())?)?,
+                    aim,
+                )),
+                Down(x) => Some((distance, depth, aim.checked_add(x.into())?)),
+                Up(x) => Some((distance, depth, aim.checked_sub(x.into())?)),
+            },
+        )
+        .ok_or(Overflow("destination"))?;
+    Ok((pos.0, pos.1))
 }
 
-pub fn run() {
-    let path = read_input();
-    let (distance, depth) = calculate_destination(&path);
-    println!(
-        "Task1: Distance travelled: {}, current depth: {}, product {}",
-        distance,
-        depth,
-        distance * depth
-    );
-    let (distance, depth) = calculate_destination_with_aim(&path);
-    println!(
-        "Task2: Distance travelled: {}, current depth: {}, product {}",
-        distance,
-        depth,
-        distance * depth
-    );
+/// Distance travelled times the final depth
+pub fn calculate_product((distance, depth): (i64, i64)) -> Result<i64, Overflow> {
+    distance
+        .checked_mul(depth)
+        .ok_or(Overflow("distance times depth"))
+}
+
+pub struct Day2;
+
+impl Solution for Day2 {
+    type Input = Vec<Command>;
+
+    fn parse(source: &InputSource) -> Result<Self::Input, ParseError> {
+        read_input(source)
+    }
+
+    // Product of distance travelled and current depth
+    fn part1(path: &Self::Input) -> Answer {
+        calculate_destination(path)
+            .and_then(calculate_product)
+            .into()
+    }
+
+    // Product of distance travelled and current depth, when steering with aim
+    fn part2(path: &Self::Input) -> Answer {
+        calculate_destination_with_aim(path)
+            .and_then(calculate_product)
+            .into()
+    }
 }
 
 #[cfg(test)]
@@ -84,22 +140,66 @@ mod tests {
     fn test_calculate_destination() {
         use Command::*;
         let path = [Forward(5), Down(5), Forward(8), Up(3), Down(8), Forward(2)];
-        assert_eq!(calculate_destination(&path), (15, 10));
+        assert_eq!(calculate_destination(&path), Ok((15, 10)));
     }
 
     #[test]
     fn test_calculate_destination_with_aim() {
         use Command::*;
         let path = [Forward(5), Down(5), Forward(8), Up(3), Down(8), Forward(2)];
-        assert_eq!(calculate_destination_with_aim(&path), (15, 60));
+        assert_eq!(calculate_destination_with_aim(&path), Ok((15, 60)));
+    }
+
+    #[test]
+    fn test_large_destinations() {
+        use Command::*;
+        // Past i32 but well within i64
+        let path = [Forward(100_000), Down(100_000)];
+        assert_eq!(
+            Day2::part1(&path.to_vec()),
+            Answer::from(10_000_000_000_i64)
+        );
+
+        let path = [
+            Forward(i32::MAX),
+            Forward(i32::MAX),
+            Down(i32::MAX),
+            Down(i32::MAX),
+        ];
+        assert_eq!(
+            Day2::part1(&path.to_vec()),
+            Answer::Overflow(Overflow("distance times depth"))
+        );
+        let path = [Down(i32::MAX), Forward(i32::MAX), Forward(i32::MAX)];
+        assert_eq!(
+            calculate_destination_with_aim(&path.repeat(2)),
+            Err(Overflow("destination"))
+        );
     }
 
     #[test]
     fn test_parse_line() {
         use Command::*;
-        assert_eq!(parse_line("forward 2"), Some(Forward(2)));
-        assert_eq!(parse_line("up 10"), Some(Up(10)));
-        assert_eq!(parse_line("down 22021"), Some(Down(22021)));
-        assert_eq!(parse_line("dn 22021"), None);
+        assert_eq!(parse_line("forward 2", 1).ok(), Some(Forward(2)));
+        assert_eq!(parse_line("up 10", 1).ok(), Some(Up(10)));
+        assert_eq!(parse_line("down 22021", 1).ok(), Some(Down(22021)));
+        assert_eq!(parse_line("dn 22021", 1).ok(), None);
+    }
+
+    #[test]
+    fn test_parse_line_errors() {
+        assert_eq!(
+            parse_line("dn 22021", 3).unwrap_err().to_string(),
+            "line 3, column 1: expected forward, down or up, found 'dn'"
+        );
+        assert_eq!(
+            parse_line("up x", 1).unwrap_err().to_string(),
+            "line 1, column 4: 'x' is not a valid number"
+        );
+        assert_eq!(
+            parse_line("up", 1).unwrap_err().to_string(),
+            "line 1, column 3: expected distance"
+        );
+        assert!(parse_line("up 1 2", 1).is_err());
     }
 }
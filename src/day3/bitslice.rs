@@ -0,0 +1,250 @@
+//! Bit-sliced diagnostic reports, stored column by column so the bits of 64 rows are counted
+//! with a single popcount.
+
+use super::{
+    co2_criteria, get_position_mask, get_sequence_from_counts, oxygen_criteria, AmbiguousBit,
+    PositionCount, TiePolicy,
+};
+
+const WORD_BITS: usize = u64::BITS as usize;
+
+/// The transposed report, where bit `r` of column `p` is bit `p` of row `r`
+#[derive(Debug, PartialEq)]
+pub struct BitSlicedReport {
+    // One column per bit position, rightmost position first
+    columns: Vec<Vec<u64>>,
+    rows: usize,
+    width: u32,
+}
+
+impl BitSlicedReport {
+    /// Report of rows that are `width` bits wide
+    ///
+    /// Panics if `width` is more than 64 or a row has bits set at or above `width`.
+    pub fn from_rows(rows: &[u64], width: u32) -> Self {
+        assert!(width <= u64::BITS, "Rows are at most 64 bits wide");
+        let words = rows.len().div_ceil(WORD_BITS);
+        let mut columns = vec![vec![0_u64; words]; width as usize];
+        for (row_index, row) in rows.iter().enumerate() {
+            assert!(
+                u64::BITS - row.leading_zeros() <= width,
+                "Row {} is wider than {} bits",
+                row_index,
+                width
+            );
+            let row_mask = 1 << (row_index % WORD_BITS);
+            // Only visit the positions that are set
+            let mut remaining = *row;
+            while remaining != 0 {
+                let position = remaining.trailing_zeros() as usize;
+                columns[position][row_index / WORD_BITS] |= row_mask;
+                remaining &= remaining - 1;
+            }
+        }
+        Self {
+            columns,
+            rows: rows.len(),
+            width,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.rows
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows == 0
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// The row at `index`, gathered back from the columns
+    pub fn row(&self, index: usize) -> u64 {
+        let row_mask = 1 << (index % WORD_BITS);
+        self.columns
+            .iter()
+            .enumerate()
+            .filter(|(_, column)| column[index / WORD_BITS] & row_mask > 0)
+            .fold(0, |acc, (position, _)| {
+                acc | get_position_mask(position as u32)
+            })
+    }
+
+    /// Counts of ones and zeros at every position, leftmost position first
+    pub fn position_counts(&self) -> Vec<PositionCount> {
+        (0..self.width)
+            .rev()
+            .map(|position| {
+                let ones = count_ones(&self.columns[position as usize]);
+                PositionCount {
+                    position,
+                    ones,
+                    zeros: self.rows - ones,
+                }
+            })
+            .collect()
+    }
+
+    /// The gamma rate, see `get_sequence_of_most_common_bits`
+    pub fn most_common_bits(&self, policy: TiePolicy) -> Result<u64, AmbiguousBit> {
+        get_sequence_from_counts(&self.position_counts(), |count| count.most_common(policy))
+    }
+
+    /// The epsilon rate, see `get_sequence_of_least_common_bits`
+    pub fn least_common_bits(&self, policy: TiePolicy) -> Result<u64, AmbiguousBit> {
+        get_sequence_from_counts(&self.position_counts(), |count| count.least_common(policy))
+    }
+
+    /// Oxygen generator rating, see `get_oxygen_rating`
+    pub fn oxygen_rating(&self) -> Option<u64> {
+        self.filter_by_bit_criteria(oxygen_criteria)
+    }
+
+    /// CO2 scrubber rating, see `get_co2_rating`
+    pub fn co2_rating(&self) -> Option<u64> {
+        self.filter_by_bit_criteria(co2_criteria)
+    }
+
+    // Same filter as the row based one, with the remaining rows kept as a bit mask
+    fn filter_by_bit_criteria(&self, select: fn(&PositionCount) -> Option<bool>) -> Option<u64> {
+        let mut remaining = self.all_rows_mask();
+        let mut remaining_count = self.rows;
+        for position in (0..self.width).rev() {
+            if remaining_count == 1 {
+                break;
+            }
+            let column = &self.columns[position as usize];
+            let ones = column
+                .iter()
+                .zip(remaining.iter())
+                .map(|(c, r)| (c & r).count_ones() as usize)
+                .sum::<usize>();
+            let count = PositionCount {
+                position,
+                ones,
+                zeros: remaining_count - ones,
+            };
+            // When all rows share the bit, the least common one would filter out every row
+            if count.ones == 0 || count.zeros == 0 {
+                continue;
+            }
+            let keep_ones = select(&count).expect("Ties are resolved by the rating criteria");
+            for (r, c) in remaining.iter_mut().zip(column.iter()) {
+                *r &= match keep_ones {
+                    true => *c,
+                    false => !*c,
+                };
+            }
+            remaining_count = match keep_ones {
+                true => count.ones,
+                false => count.zeros,
+            };
+        }
+
+        let (word_index, word) = remaining.iter().enumerate().find(|(_, word)| **word != 0)?;
+        Some(self.row(word_index * WORD_BITS + word.trailing_zeros() as usize))
+    }
+
+    // Set bits for every row, leaving the unused bits of the last word clear
+    fn all_rows_mask(&self) -> Vec<u64> {
+        let mut mask = vec![u64::MAX; self.rows.div_ceil(WORD_BITS)];
+        if let Some(last) = mask.last_mut() {
+            let used_bits = self.rows % WORD_BITS;
+            if used_bits != 0 {
+                *last = (1 << used_bits) - 1;
+            }
+        }
+        mask
+    }
+}
+
+fn count_ones(words: &[u64]) -> usize {
+    words.iter().map(|w| w.count_ones() as usize).sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::day3::{
+        get_co2_rating, get_oxygen_rating, get_position_counts, get_sequence_of_least_common_bits,
+        get_sequence_of_most_common_bits, Day3,
+    };
+    use crate::solution::{Answer, Solution};
+    use crate::testing::xorshift;
+
+    fn get_input() -> Vec<u64> {
+        vec![
+            0b00100, 0b11110, 0b10110, 0b10111, 0b10101, 0b01111, 0b00111, 0b11100, 0b10000,
+            0b11001, 0b00010, 0b01010,
+        ]
+    }
+
+    // More rows than fit in one word, so the masks of partial words are exercised
+    fn get_generated_input(rows: usize, width: u32) -> Vec<u64> {
+        xorshift(0x2545_f491_4f6c_dd1d)
+            .take(rows)
+            .map(|value| value >> (u64::BITS - width))
+            .collect()
+    }
+
+    #[test]
+    fn test_row_round_trip() {
+        let input = get_input();
+        let report = BitSlicedReport::from_rows(&input, 5);
+        assert_eq!(report.len(), 12);
+        assert_eq!(report.width(), 5);
+        for (index, row) in input.iter().enumerate() {
+            assert_eq!(report.row(index), *row);
+        }
+    }
+
+    #[test]
+    fn test_empty_report() {
+        let report = BitSlicedReport::from_rows(&[], 5);
+        assert!(report.is_empty());
+        assert_eq!(report.oxygen_rating(), None);
+        assert_eq!(report.co2_rating(), None);
+        assert_eq!(
+            Day3::part2(&report),
+            Answer::NoSolution("the report has no rows".to_string())
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Row 1 is wider than 5 bits")]
+    fn test_row_wider_than_width() {
+        BitSlicedReport::from_rows(&[0b10110, 0b100000], 5);
+    }
+
+    #[test]
+    fn test_sample() {
+        let report = BitSlicedReport::from_rows(&get_input(), 5);
+        assert_eq!(report.most_common_bits(TiePolicy::Ambiguous), Ok(22));
+        assert_eq!(report.least_common_bits(TiePolicy::Ambiguous), Ok(9));
+        assert_eq!(report.oxygen_rating(), Some(23));
+        assert_eq!(report.co2_rating(), Some(10));
+    }
+
+    #[test]
+    fn test_matches_row_based_engine() {
+        for (rows, width) in [(1, 1), (63, 7), (64, 12), (1000, 12), (1001, 64)] {
+            let input = get_generated_input(rows, width);
+            let report = BitSlicedReport::from_rows(&input, width);
+            assert_eq!(report.position_counts(), get_position_counts(&input, width));
+            for policy in [TiePolicy::PreferOne, TiePolicy::PreferZero] {
+                assert_eq!(
+                    report.most_common_bits(policy),
+                    get_sequence_of_most_common_bits(&input, width, policy)
+                );
+                assert_eq!(
+                    report.least_common_bits(policy),
+                    get_sequence_of_least_common_bits(&input, width, policy)
+                );
+            }
+            assert_eq!(report.oxygen_rating(), get_oxygen_rating(&input, width));
+            assert_eq!(report.co2_rating(), get_co2_rating(&input, width));
+        }
+    }
+}
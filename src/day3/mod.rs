@@ -1,130 +1,439 @@
-use std::{fs, io::BufRead, io::BufReader};
-
-// This solution is left MSB
-// const POSITIONS_BIT_MASK: [u16; 12] = [1, 2, 4, 8, 16, 32, 64, 128, 256, 512, 1024, 2048];
-const POSITIONS_BIT_MASK: [u16; 12] = [2048, 1024, 512, 256, 128, 64, 32, 16, 8, 4, 2, 1];
-
-fn read_input() -> Vec<u16> {
-    let filename = "src/day3/input";
-    let file = fs::File::open(filename).unwrap();
-    let contents = BufReader::new(file);
-    contents
-        .lines()
-        .filter_map(|r| match r {
-            Ok(s) => u16::from_str_radix(&s, 2).ok(),
-            Err(_) => None,
-        })
-        .collect::<Vec<_>>()
+//! Day 3: Binary Diagnostic
+
+pub mod bitslice;
+
+use std::{cmp::Ordering, error::Error, fmt};
+
+use bitslice::BitSlicedReport;
+
+use crate::input::InputSource;
+use crate::parse::{numbered_lines, single_token, ParseError};
+use crate::solution::{Answer, Solution};
+
+/// Widest row a diagnostic report can have
+pub const MAX_WIDTH: u32 = u64::BITS;
+
+// Rows of the diagnostic report all have the same number of bits
+fn read_input(source: &InputSource) -> Result<BitSlicedReport, ParseError> {
+    let contents = source.open(3)?;
+    let mut bits = Vec::new();
+    let mut width = None;
+    for line in numbered_lines(contents) {
+        let (line_number, line) = line?;
+        if let Some((column, token)) = single_token(&line, line_number)? {
+            let row = parse_binary(token, line_number, column)?;
+            // Binary digits are one byte each, so the length in bytes is the number of bits
+            let expected_width = *width.get_or_insert(token.len());
+            if token.len() != expected_width {
+                return Err(ParseError::WrongLength {
+                    line: line_number,
+                    item: "bits",
+                    expected: expected_width,
+                    found: token.len(),
+                });
+            }
+            bits.push(row);
+        }
+    }
+    match width {
+        Some(width) => Ok(BitSlicedReport::from_rows(&bits, width as u32)),
+        None => Err(ParseError::EmptyInput),
+    }
 }
 
-fn get_sequence_of_most_common_bits(bits: &[u16]) -> u16 {
-    let mut n_bits_per_position = Vec::new();
-    for pos in POSITIONS_BIT_MASK {
-        n_bits_per_position.push(bits.iter().fold(0_u16, |acc, b| match b & pos > 0 {
-            true => acc + 1,
-            _ => acc,
-        }));
+fn parse_binary(token: &str, line: usize, column: usize) -> Result<u64, ParseError> {
+    if let Some((idx, c)) = token.char_indices().find(|(_, c)| *c != '0' && *c != '1') {
+        return Err(ParseError::UnexpectedToken {
+            line,
+            column: column + idx,
+            token: c.to_string(),
+            expected: "binary digit",
+        });
+    }
+    // Only ASCII digits are left, so slicing by bytes cannot split a character
+    let max_width = MAX_WIDTH as usize;
+    if token.len() > max_width {
+        return Err(ParseError::UnexpectedToken {
+            line,
+            column: column + max_width,
+            token: token[max_width..].to_string(),
+            expected: "at most 64 bits",
+        });
     }
+    u64::from_str_radix(token, 2).map_err(|_| ParseError::InvalidNumber {
+        line,
+        column,
+        token: token.to_string(),
+    })
+}
 
-    let half_number_of_rows = (bits.len() / 2) as u16;
-    n_bits_per_position
-        .iter()
-        .zip(POSITIONS_BIT_MASK.iter())
-        .fold(0_u16, |acc, (n_bits, pos)| {
-            match n_bits > &half_number_of_rows {
-                true => acc + pos,
-                _ => acc,
+// Positions are counted from the rightmost, least significant, bit
+fn get_position_mask(position: u32) -> u64 {
+    1 << position
+}
+
+/// How to pick the most or least common bit when ones and zeros are equally common
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TiePolicy {
+    PreferOne,
+    PreferZero,
+    /// Report the tie as an error
+    Ambiguous,
+}
+
+/// Number of ones and zeros at one bit position, counted from the rightmost bit
+#[derive(Debug, PartialEq)]
+pub struct PositionCount {
+    pub position: u32,
+    pub ones: usize,
+    pub zeros: usize,
+}
+
+impl PositionCount {
+    /// Whether 1 is the most common bit, None on a tie with `TiePolicy::Ambiguous`
+    pub fn most_common(&self, policy: TiePolicy) -> Option<bool> {
+        match self.ones.cmp(&self.zeros) {
+            Ordering::Greater => Some(true),
+            Ordering::Less => Some(false),
+            Ordering::Equal => resolve_tie(policy),
+        }
+    }
+
+    /// Whether 1 is the least common bit, None on a tie with `TiePolicy::Ambiguous`
+    pub fn least_common(&self, policy: TiePolicy) -> Option<bool> {
+        match self.ones.cmp(&self.zeros) {
+            Ordering::Greater => Some(false),
+            Ordering::Less => Some(true),
+            Ordering::Equal => resolve_tie(policy),
+        }
+    }
+}
+
+impl fmt::Display for PositionCount {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "position {}: {} ones, {} zeros",
+            self.position, self.ones, self.zeros
+        )
+    }
+}
+
+fn resolve_tie(policy: TiePolicy) -> Option<bool> {
+    match policy {
+        TiePolicy::PreferOne => Some(true),
+        TiePolicy::PreferZero => Some(false),
+        TiePolicy::Ambiguous => None,
+    }
+}
+
+/// A bit position where ones and zeros are equally common, under `TiePolicy::Ambiguous`
+#[derive(Debug, PartialEq)]
+pub struct AmbiguousBit {
+    pub position: u32,
+    pub count: usize,
+}
+
+impl fmt::Display for AmbiguousBit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "position {} has {} ones and {} zeros",
+            self.position, self.count, self.count
+        )
+    }
+}
+
+impl Error for AmbiguousBit {}
+
+/// Counts of ones and zeros at every position of `size` bits, leftmost position first
+pub fn get_position_counts(bits: &[u64], size: u32) -> Vec<PositionCount> {
+    (0..size)
+        .rev()
+        .map(|position| {
+            let ones = get_ones_at_position(bits, position);
+            PositionCount {
+                position,
+                ones,
+                zeros: bits.len() - ones,
             }
         })
+        .collect()
 }
 
-fn get_full_bit_mask(size: u16) -> u16 {
-    let mut bit_mask = 0;
-    for i in 0..size {
-        bit_mask += 2_u16.pow(i.into());
-    }
-    bit_mask
+// Sets every position of `size` bits to the bit `select` picks from its counts
+fn get_sequence_of_selected_bits(
+    bits: &[u64],
+    size: u32,
+    select: impl Fn(&PositionCount) -> Option<bool>,
+) -> Result<u64, AmbiguousBit> {
+    get_sequence_from_counts(&get_position_counts(bits, size), select)
+}
+
+fn get_sequence_from_counts(
+    counts: &[PositionCount],
+    select: impl Fn(&PositionCount) -> Option<bool>,
+) -> Result<u64, AmbiguousBit> {
+    counts
+        .iter()
+        .try_fold(0_u64, |acc, count| match select(count) {
+            Some(true) => Ok(acc | get_position_mask(count.position)),
+            Some(false) => Ok(acc),
+            None => Err(AmbiguousBit {
+                position: count.position,
+                count: count.ones,
+            }),
+        })
 }
 
-fn get_sequence_of_least_common_bits(bits: &[u16], size: u16) -> u16 {
-    let most_common = get_sequence_of_most_common_bits(bits);
-    let bit_mask = get_full_bit_mask(size);
+/// The gamma rate, the most common bit at every position of `size` bits
+pub fn get_sequence_of_most_common_bits(
+    bits: &[u64],
+    size: u32,
+    policy: TiePolicy,
+) -> Result<u64, AmbiguousBit> {
+    get_sequence_of_selected_bits(bits, size, |count| count.most_common(policy))
+}
 
-    !most_common & bit_mask
+/// The epsilon rate, the least common bit at every position of `size` bits
+pub fn get_sequence_of_least_common_bits(
+    bits: &[u64],
+    size: u32,
+    policy: TiePolicy,
+) -> Result<u64, AmbiguousBit> {
+    get_sequence_of_selected_bits(bits, size, |count| count.least_common(policy))
 }
 
-fn get_ones_at_position(bits: &[u16], position: u16) -> u16 {
-    let bit_mask = POSITIONS_BIT_MASK[POSITIONS_BIT_MASK.len() - position as usize - 1];
+fn get_ones_at_position(bits: &[u64], position: u32) -> usize {
+    let bit_mask = get_position_mask(position);
     bits.iter().fold(0, |acc, b| match b & bit_mask > 0 {
         true => acc + 1,
         false => acc,
     })
 }
 
-fn get_oxygen_rating(bits: &[u16], size: u16) -> u16 {
-    let mut remaining_sequences = bits;
-    for i in size..0 {
-        get_ones_at_position(remaining_sequences, i);
-
-        // for j in 0..remaining_sequences {}
+// Keeps filtering rows by the bit `select` picks, from the leftmost position of `size` bits,
+// until a single row is left, None if there are no rows
+fn filter_by_bit_criteria(
+    bits: &[u64],
+    size: u32,
+    select: impl Fn(&PositionCount) -> Option<bool>,
+) -> Option<u64> {
+    let mut remaining_sequences = bits.to_vec();
+    for i in (0..size).rev() {
+        if remaining_sequences.len() == 1 {
+            break;
+        }
+        let ones = get_ones_at_position(&remaining_sequences, i);
+        let count = PositionCount {
+            position: i,
+            ones,
+            zeros: remaining_sequences.len() - ones,
+        };
+        // When all rows share the bit, the least common one would filter out every row
+        if count.ones == 0 || count.zeros == 0 {
+            continue;
+        }
+        let bit_mask = get_position_mask(i);
+        let keep_ones = select(&count).expect("Ties are resolved by the rating criteria");
+        remaining_sequences.retain(|b| (b & bit_mask > 0) == keep_ones);
     }
+    remaining_sequences.first().copied()
+}
 
-    0
+fn oxygen_criteria(count: &PositionCount) -> Option<bool> {
+    count.most_common(TiePolicy::PreferOne)
 }
 
-pub fn run() {
-    let bits = read_input();
-    assert_eq!(bits.len(), 1000);
-    let gamma_rate = get_sequence_of_most_common_bits(&bits);
-    let epsilon_rate = get_sequence_of_least_common_bits(&bits, 12);
+fn co2_criteria(count: &PositionCount) -> Option<bool> {
+    count.least_common(TiePolicy::PreferZero)
+}
 
-    let power_consumption: u32 = gamma_rate as u32 * epsilon_rate as u32;
+/// Oxygen generator rating, keeping the most common bit and 1 on ties, None without rows
+pub fn get_oxygen_rating(bits: &[u64], size: u32) -> Option<u64> {
+    filter_by_bit_criteria(bits, size, oxygen_criteria)
+}
 
-    println!(
-        "Task1: Gamme rate {}, Epsilon rate {}, Total {}",
-        gamma_rate, epsilon_rate, power_consumption
-    );
+/// CO2 scrubber rating, keeping the least common bit and 0 on ties, None without rows
+pub fn get_co2_rating(bits: &[u64], size: u32) -> Option<u64> {
+    filter_by_bit_criteria(bits, size, co2_criteria)
+}
+
+pub struct Day3;
+
+impl Solution for Day3 {
+    type Input = BitSlicedReport;
+
+    fn parse(source: &InputSource) -> Result<Self::Input, ParseError> {
+        read_input(source)
+    }
+
+    // Power consumption, gamma rate times epsilon rate, resolving ties like the ratings do
+    fn part1(report: &Self::Input) -> Answer {
+        let gamma_rate = report
+            .most_common_bits(TiePolicy::PreferOne)
+            .expect("Ties resolve to one");
+        let epsilon_rate = report
+            .least_common_bits(TiePolicy::PreferZero)
+            .expect("Ties resolve to zero");
+
+        let power_consumption: u128 = gamma_rate as u128 * epsilon_rate as u128;
+        power_consumption.into()
+    }
+
+    // Life support rating, oxygen generator rating times CO2 scrubber rating
+    fn part2(report: &Self::Input) -> Answer {
+        match (report.oxygen_rating(), report.co2_rating()) {
+            (Some(oxygen_rating), Some(co2_rating)) => {
+                let life_support_rating: u128 = oxygen_rating as u128 * co2_rating as u128;
+                life_support_rating.into()
+            }
+            _ => Answer::NoSolution("the report has no rows".to_string()),
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    fn get_input() -> Vec<u16> {
+    fn get_input() -> Vec<u64> {
         vec![
             0b00100, 0b11110, 0b10110, 0b10111, 0b10101, 0b01111, 0b00111, 0b11100, 0b10000,
             0b11001, 0b00010, 0b01010,
         ]
     }
 
+    #[test]
+    fn test_parse_binary() {
+        assert_eq!(parse_binary("10110", 1, 1).unwrap(), 22);
+        assert_eq!(
+            parse_binary("10120", 2, 1).unwrap_err().to_string(),
+            "line 2, column 4: expected binary digit, found '2'"
+        );
+        assert_eq!(
+            parse_binary(&format!("{}é", "0".repeat(63)), 1, 1)
+                .unwrap_err()
+                .to_string(),
+            "line 1, column 64: expected binary digit, found 'é'"
+        );
+        assert_eq!(
+            parse_binary(&"1".repeat(66), 3, 2).unwrap_err().to_string(),
+            "line 3, column 66: expected at most 64 bits, found '11'"
+        );
+    }
+
     #[test]
     fn test_get_most_common_bit() {
         let input = get_input();
-        assert_eq!(get_sequence_of_most_common_bits(&input), 22);
+        assert_eq!(
+            get_sequence_of_most_common_bits(&input, 5, TiePolicy::Ambiguous),
+            Ok(22)
+        );
     }
 
     #[test]
-    fn test_get_bit_mask() {
+    fn test_get_least_common_bit() {
         let input = get_input();
-        assert_eq!(get_full_bit_mask(5), 31);
+        assert_eq!(
+            get_sequence_of_least_common_bits(&input, 5, TiePolicy::Ambiguous),
+            Ok(9)
+        );
     }
 
     #[test]
-    fn test_get_least_common_bit() {
+    fn test_get_position_counts() {
         let input = get_input();
-        assert_eq!(get_sequence_of_least_common_bits(&input, 5), 9);
+        let counts = get_position_counts(&input, 5);
+        assert_eq!(
+            counts[0],
+            PositionCount {
+                position: 4,
+                ones: 7,
+                zeros: 5
+            }
+        );
+        assert_eq!(counts[4].to_string(), "position 0: 5 ones, 7 zeros");
+    }
+
+    #[test]
+    fn test_ties_with_even_number_of_rows() {
+        let input = [0b10, 0b01, 0b11, 0b00];
+        assert_eq!(
+            get_sequence_of_most_common_bits(&input, 2, TiePolicy::PreferOne),
+            Ok(0b11)
+        );
+        assert_eq!(
+            get_sequence_of_most_common_bits(&input, 2, TiePolicy::PreferZero),
+            Ok(0b00)
+        );
+        assert_eq!(
+            get_sequence_of_least_common_bits(&input, 2, TiePolicy::PreferOne),
+            Ok(0b11)
+        );
+        assert_eq!(
+            get_sequence_of_least_common_bits(&input, 2, TiePolicy::Ambiguous),
+            Err(AmbiguousBit {
+                position: 1,
+                count: 2
+            })
+        );
+        assert_eq!(
+            get_sequence_of_most_common_bits(&[0b11, 0b10], 2, TiePolicy::Ambiguous)
+                .unwrap_err()
+                .to_string(),
+            "position 0 has 1 ones and 1 zeros"
+        );
+    }
+
+    #[test]
+    fn test_no_ties_with_odd_number_of_rows() {
+        let input = [0b10, 0b11, 0b00];
+        for policy in [
+            TiePolicy::PreferOne,
+            TiePolicy::PreferZero,
+            TiePolicy::Ambiguous,
+        ] {
+            assert_eq!(
+                get_sequence_of_most_common_bits(&input, 2, policy),
+                Ok(0b10)
+            );
+            assert_eq!(
+                get_sequence_of_least_common_bits(&input, 2, policy),
+                Ok(0b01)
+            );
+        }
     }
 
     #[test]
     fn test_task1() {
         let input = get_input();
 
-        let gamma = get_sequence_of_most_common_bits(&input);
-        let epsilon = get_sequence_of_least_common_bits(&input, 5);
+        let gamma = get_sequence_of_most_common_bits(&input, 5, TiePolicy::PreferOne).unwrap();
+        let epsilon = get_sequence_of_least_common_bits(&input, 5, TiePolicy::PreferZero).unwrap();
         let fuel_consumption: u32 = gamma as u32 * epsilon as u32;
         assert_eq!(fuel_consumption, 198);
     }
 
+    #[test]
+    fn test_64_bit_rows() {
+        let input = vec![u64::MAX, 1 << 63, (1 << 63) | 1];
+        assert_eq!(
+            parse_binary(&format!("{:064b}", u64::MAX), 1, 1).unwrap(),
+            u64::MAX
+        );
+        assert_eq!(
+            get_sequence_of_most_common_bits(&input, 64, TiePolicy::Ambiguous),
+            Ok((1 << 63) | 1)
+        );
+        assert_eq!(
+            get_sequence_of_least_common_bits(&input, 64, TiePolicy::Ambiguous),
+            Ok(!((1 << 63) | 1))
+        );
+        assert_eq!(get_oxygen_rating(&input, 64), Some((1 << 63) | 1));
+        assert_eq!(get_co2_rating(&input, 64), Some(u64::MAX));
+    }
+
     #[test]
     fn test_get_ones_at_position() {
         let input = get_input();
@@ -134,6 +443,24 @@ mod tests {
     #[test]
     fn test_get_oxygen_rating() {
         let input = get_input();
-        assert_eq!(get_oxygen_rating(&input, 5), 23);
+        assert_eq!(get_oxygen_rating(&input, 5), Some(23));
+        assert_eq!(get_oxygen_rating(&[], 5), None);
+    }
+
+    #[test]
+    fn test_get_co2_rating() {
+        let input = get_input();
+        assert_eq!(get_co2_rating(&input, 5), Some(10));
+        assert_eq!(get_co2_rating(&[], 5), None);
+    }
+
+    #[test]
+    fn test_task2() {
+        let input = get_input();
+
+        let oxygen = get_oxygen_rating(&input, 5).unwrap();
+        let co2 = get_co2_rating(&input, 5).unwrap();
+        let life_support_rating: u32 = oxygen as u32 * co2 as u32;
+        assert_eq!(life_support_rating, 230);
     }
 }
@@ -1,101 +1,150 @@
-use std::{fs, io::BufRead, io::BufReader};
+//! Day 4: Giant Squid
 
-type BoardView = [[u8; 5]; 5];
+use crate::grid::Grid;
+use crate::input::InputSource;
+use crate::parse::{numbered_lines, parse_number, tokens, ParseError};
+use crate::solution::{Answer, Solution};
 
-#[derive(Debug, PartialEq, Clone, Copy)]
-struct Board {
-    rows: BoardView,
-    columns: BoardView,
+pub type BoardView = [[u8; 5]; 5];
+
+/// A 5x5 bingo board, kept both row and column wise
+#[derive(Debug, PartialEq, Clone)]
+pub struct Board {
+    rows: Grid<u8>,
+    // The transposed board, so columns can be checked as slices like the rows
+    columns: Grid<u8>,
 }
 
 impl Board {
-    fn from_matrix(matrix: BoardView) -> Self {
-        let mut columns: BoardView = [[0; 5]; 5];
-        for i in 0..matrix.len() {
-            for j in 0..matrix[0].len() {
-                columns[i][j] = matrix[j][i];
-            }
-        }
-        Self {
-            rows: matrix,
-            columns,
-        }
+    pub fn from_matrix(matrix: BoardView) -> Self {
+        Self::from_grid(Grid::from_rows(
+            matrix.iter().map(|row| row.to_vec()).collect(),
+        ))
     }
 
-    fn from_values(values: &[u8]) -> Self {
+    /// Board from 25 values in row order
+    pub fn from_values(values: &[u8]) -> Self {
         assert_eq!(values.len(), 25);
+        Self::from_grid(Grid::from_vec(5, values.to_vec()))
+    }
 
-        let mut rows: BoardView = [[0; 5]; 5];
-        for (row_idx, row) in values.chunks(5).enumerate() {
-            for (column_idx, v) in row.iter().enumerate() {
-                rows[row_idx][column_idx] = *v;
-            }
-        }
-        Self::from_matrix(rows)
+    fn from_grid(rows: Grid<u8>) -> Self {
+        let columns = rows.transpose();
+        Self { rows, columns }
     }
 
-    fn rows(&self) -> &BoardView {
-        &self.rows
+    pub fn rows(&self) -> impl Iterator<Item = &[u8]> {
+        self.rows.rows()
     }
 
-    fn columns(&self) -> &BoardView {
-        &self.columns
+    pub fn columns(&self) -> impl Iterator<Item = &[u8]> {
+        self.columns.rows()
     }
 
-    fn values(&self) -> Vec<u8> {
-        self.rows.iter().flatten().map(|x| *x).collect()
+    pub fn values(&self) -> Vec<u8> {
+        self.rows.iter().copied().collect()
     }
 }
 
-fn read_input() -> (Vec<u8>, Vec<Board>) {
-    let filename = "src/day4/input";
-    let file = fs::File::open(filename).unwrap();
-    let mut contents = BufReader::new(file);
-    let mut string_buffer = String::new();
-    contents
-        .read_line(&mut string_buffer)
-        .expect("Failed to read inputs");
-    let first_line = string_buffer
-        .split(',')
-        .filter_map(|r| r.parse::<u8>().ok())
-        .collect::<Vec<u8>>();
-
-    string_buffer.clear();
+fn read_input(source: &InputSource) -> Result<(Vec<u8>, Vec<Board>), ParseError> {
+    let contents = source.open(4)?;
+    let mut lines = numbered_lines(contents);
+
+    let (line_number, first_line) = lines.next().ok_or(ParseError::EmptyInput)??;
+    let first_line = tokens(&first_line, |c| c == ',' || c.is_whitespace())
+        .map(|(column, token)| parse_number::<u8>(token, line_number, column))
+        .collect::<Result<Vec<u8>, ParseError>>()?;
+    if first_line.is_empty() {
+        return Err(ParseError::MissingToken {
+            line: line_number,
+            column: 1,
+            expected: "drawn numbers",
+        });
+    }
 
     let mut boards = Vec::new();
-    while let Ok(bytes_read) = contents.read_line(&mut string_buffer) {
-        if bytes_read == 1 {
-            let parsed_matrix = string_buffer
-                .split_whitespace()
-                .filter_map(|r| r.parse::<u8>().ok())
-                .collect::<Vec<u8>>();
-
-            if !parsed_matrix.is_empty() {
-                boards.push(Board::from_values(&parsed_matrix));
+    let mut values = Vec::new();
+    let mut last_line_number = line_number;
+    for line in lines {
+        let (line_number, line) = line?;
+        last_line_number = line_number;
+        if line.trim().is_empty() {
+            if !values.is_empty() {
+                boards.push(finish_board(&values, line_number)?);
+                values.clear();
             }
-            string_buffer.clear();
-        } else if bytes_read == 0 {
-            break;
+            continue;
         }
+        if values.len() == 25 {
+            return Err(ParseError::UnexpectedToken {
+                line: line_number,
+                column: 1,
+                token: line.trim().to_string(),
+                expected: "blank line after 5 board rows",
+            });
+        }
+        values.append(&mut parse_board_row(&line, line_number)?);
+    }
+    if !values.is_empty() {
+        boards.push(finish_board(&values, last_line_number + 1)?);
+    }
+    if boards.is_empty() {
+        return Err(ParseError::MissingToken {
+            line: last_line_number + 1,
+            column: 1,
+            expected: "bingo board",
+        });
     }
-    assert_eq!(boards.len(), 100);
-    (first_line, boards)
+    Ok((first_line, boards))
+}
+
+fn parse_board_row(line: &str, line_number: usize) -> Result<Vec<u8>, ParseError> {
+    let mut row = Vec::new();
+    for (column, token) in tokens(line, char::is_whitespace) {
+        if row.len() == 5 {
+            return Err(ParseError::UnexpectedToken {
+                line: line_number,
+                column,
+                token: token.to_string(),
+                expected: "5 numbers per board row",
+            });
+        }
+        row.push(parse_number(token, line_number, column)?);
+    }
+    if row.len() < 5 {
+        return Err(ParseError::MissingToken {
+            line: line_number,
+            column: line.len() + 1,
+            expected: "5 numbers per board row",
+        });
+    }
+    Ok(row)
+}
+
+// `line_number` is the line following the board, where the missing rows were expected
+fn finish_board(values: &[u8], line_number: usize) -> Result<Board, ParseError> {
+    if values.len() < 25 {
+        return Err(ParseError::MissingToken {
+            line: line_number,
+            column: 1,
+            expected: "5 rows per board",
+        });
+    }
+    Ok(Board::from_values(values))
 }
 
 fn find_horizontal_bingo(boards: &[Board], inputs: &[u8]) -> Option<usize> {
-    let mut board_index = 0;
-    for board in boards {
+    for (board_index, board) in boards.iter().enumerate() {
         for row in board.rows() {
             if has_bingo(row, inputs) {
                 return Some(board_index);
             }
         }
-        board_index += 1;
     }
     None
 }
 
-fn has_bingo(array: &[u8; 5], inputs: &[u8]) -> bool {
+fn has_bingo(array: &[u8], inputs: &[u8]) -> bool {
     let mut n_matches = 0;
     for v in array {
         if inputs.contains(v) {
@@ -109,40 +158,59 @@ fn has_bingo(array: &[u8; 5], inputs: &[u8]) -> bool {
 }
 
 fn find_vertical_bingo(boards: &[Board], inputs: &[u8]) -> Option<usize> {
-    let mut board_index = 0;
-    for board in boards {
+    for (board_index, board) in boards.iter().enumerate() {
         for columns in board.columns() {
             if has_bingo(columns, inputs) {
                 return Some(board_index);
             }
         }
-        board_index += 1;
     }
     None
 }
 
-pub fn run() {
-    let (inputs, boards) = read_input();
-    let bingo = find_first_bingo(&boards, &inputs);
-    if bingo.is_none() {
-        panic!("No bingo");
+pub struct Day4;
+
+impl Solution for Day4 {
+    type Input = (Vec<u8>, Vec<Board>);
+
+    fn parse(source: &InputSource) -> Result<Self::Input, ParseError> {
+        read_input(source)
+    }
+
+    // Final score of the first board to get bingo
+    fn part1((inputs, boards): &Self::Input) -> Answer {
+        match find_first_bingo(boards, inputs) {
+            Some(bingo) => {
+                calculate_answer(&boards[bingo.board_index], &inputs[0..=bingo.input_index]).into()
+            }
+            None => no_bingo(),
+        }
+    }
+
+    // Final score of the last board to get bingo
+    fn part2((inputs, boards): &Self::Input) -> Answer {
+        match find_last_bingo(boards, inputs) {
+            Some(bingo) => {
+                calculate_answer(&boards[bingo.board_index], &inputs[0..=bingo.input_index]).into()
+            }
+            None => no_bingo(),
+        }
     }
-    let bingo = bingo.unwrap();
-    let answer = calculate_answer(&boards[bingo.board_index], &inputs[0..=bingo.input_index]);
-    println!("Task1: Final score is {}", answer);
+}
 
-    let bingo = find_last_bingo(&boards, &inputs).unwrap();
-    let answer = calculate_answer(&boards[bingo.board_index], &inputs[0..=bingo.input_index]);
-    println!("Task2: Final score is {}", answer);
+fn no_bingo() -> Answer {
+    Answer::NoSolution("no board gets bingo".to_string())
 }
 
+/// The board that got bingo, and the index of the number that completed it
 #[derive(PartialEq, Debug, Default)]
-struct Indices {
-    board_index: usize,
-    input_index: usize,
+pub struct Indices {
+    pub board_index: usize,
+    pub input_index: usize,
 }
 
-fn find_first_bingo(boards: &[Board], inputs: &[u8]) -> Option<Indices> {
+/// The first board to get bingo when drawing `inputs` in order
+pub fn find_first_bingo(boards: &[Board], inputs: &[u8]) -> Option<Indices> {
     for last_index in 4..inputs.len() {
         let current_inputs = &inputs[0..=last_index];
         if let Some(winning_board_index) = find_horizontal_bingo(boards, current_inputs) {
@@ -161,7 +229,8 @@ fn find_first_bingo(boards: &[Board], inputs: &[u8]) -> Option<Indices> {
     None
 }
 
-fn calculate_answer(board: &Board, inputs: &[u8]) -> u32 {
+/// Sum of the unmarked numbers times the last number drawn
+pub fn calculate_answer(board: &Board, inputs: &[u8]) -> u32 {
     let sum_of_unmarked_numers = board
         .values()
         .iter()
@@ -171,28 +240,23 @@ fn calculate_answer(board: &Board, inputs: &[u8]) -> u32 {
     sum_of_unmarked_numers * *inputs.last().unwrap() as u32
 }
 
-fn find_last_bingo(boards: &[Board], inputs: &[u8]) -> Option<Indices> {
-    let mut boards_without_bingo = boards.iter().map(|b| *b).collect::<Vec<Board>>();
-
-    while boards_without_bingo.len() > 1 {
-        if let Some(idx) = find_first_bingo(&boards_without_bingo, &inputs) {
-            boards_without_bingo.remove(idx.board_index);
-        }
+/// The last board to get bingo when drawing `inputs` in order, boards that never get bingo
+/// are left out
+pub fn find_last_bingo(boards: &[Board], inputs: &[u8]) -> Option<Indices> {
+    let mut boards_without_bingo = boards.to_vec();
+    // Index in `boards` of every board in `boards_without_bingo`
+    let mut board_indices = (0..boards.len()).collect::<Vec<usize>>();
+    let mut last_bingo = None;
+
+    // Boards win in order, until only boards that never win are left
+    while let Some(bingo) = find_first_bingo(&boards_without_bingo, inputs) {
+        boards_without_bingo.remove(bingo.board_index);
+        last_bingo = Some(Indices {
+            board_index: board_indices.remove(bingo.board_index),
+            input_index: bingo.input_index,
+        });
     }
-
-    let board_index = boards.iter().position(|b| b == &boards_without_bingo[0]);
-
-    let input_index = find_first_bingo(
-        &boards[board_index.unwrap()..=board_index.unwrap()],
-        &inputs,
-    )
-    .unwrap()
-    .input_index;
-
-    Some(Indices {
-        board_index: board_index.unwrap(),
-        input_index,
-    })
+    last_bingo
 }
 
 #[cfg(test)]
@@ -200,29 +264,29 @@ mod tests {
     use super::*;
 
     fn get_boards() -> Vec<Board> {
-        let mut boards = Vec::new();
-        boards.push(Board::from_matrix([
-            [22, 13, 17, 11, 0],
-            [8, 2, 23, 4, 24],
-            [21, 9, 14, 16, 7],
-            [6, 10, 3, 18, 5],
-            [1, 12, 20, 15, 19],
-        ]));
-        boards.push(Board::from_matrix([
-            [3, 15, 0, 0, 22],
-            [9, 18, 13, 17, 5],
-            [19, 8, 7, 25, 23],
-            [20, 11, 10, 24, 4],
-            [14, 21, 16, 12, 6],
-        ]));
-        boards.push(Board::from_matrix([
-            [14, 21, 17, 24, 4],
-            [10, 16, 15, 9, 19],
-            [18, 8, 23, 26, 20],
-            [22, 11, 13, 6, 5],
-            [2, 0, 12, 3, 7],
-        ]));
-        boards
+        vec![
+            Board::from_matrix([
+                [22, 13, 17, 11, 0],
+                [8, 2, 23, 4, 24],
+                [21, 9, 14, 16, 7],
+                [6, 10, 3, 18, 5],
+                [1, 12, 20, 15, 19],
+            ]),
+            Board::from_matrix([
+                [3, 15, 0, 0, 22],
+                [9, 18, 13, 17, 5],
+                [19, 8, 7, 25, 23],
+                [20, 11, 10, 24, 4],
+                [14, 21, 16, 12, 6],
+            ]),
+            Board::from_matrix([
+                [14, 21, 17, 24, 4],
+                [10, 16, 15, 9, 19],
+                [18, 8, 23, 26, 20],
+                [22, 11, 13, 6, 5],
+                [2, 0, 12, 3, 7],
+            ]),
+        ]
     }
 
     fn get_inputs() -> Vec<u8> {
@@ -242,7 +306,7 @@ mod tests {
     #[test]
     fn test_get_columns() {
         let boards = get_boards();
-        assert_eq!(boards[0].columns()[0], [22, 8, 21, 6, 1])
+        assert_eq!(boards[0].columns().next().unwrap(), [22, 8, 21, 6, 1]);
     }
 
     #[test]
@@ -283,6 +347,48 @@ mod tests {
         assert_eq!(boards_from_matrix[0], board_from_value);
     }
 
+    #[test]
+    fn test_parse_board_row() {
+        assert_eq!(
+            parse_board_row("22 13 17 11  0", 3).unwrap(),
+            vec![22, 13, 17, 11, 0]
+        );
+        assert_eq!(
+            parse_board_row("22 13 17 11", 3).unwrap_err().to_string(),
+            "line 3, column 12: expected 5 numbers per board row"
+        );
+        assert_eq!(
+            parse_board_row("22 13 17 11  0 4", 3)
+                .unwrap_err()
+                .to_string(),
+            "line 3, column 16: expected 5 numbers per board row, found '4'"
+        );
+        assert!(parse_board_row("22 13 17 11 256", 3).is_err());
+    }
+
+    #[test]
+    fn test_find_last_bingo_without_winner() {
+        let boards = get_boards();
+        assert_eq!(find_last_bingo(&boards, &[99, 98, 97, 96, 95, 94]), None);
+    }
+
+    #[test]
+    fn test_tasks_with_board_that_never_wins() {
+        let mut boards = get_boards();
+        boards.push(Board::from_matrix([[99; 5]; 5]));
+        let input = (get_inputs(), boards);
+        assert_eq!(Day4::part1(&input), Answer::from(4512_u32));
+        assert_eq!(Day4::part2(&input), Answer::from(1924_u32));
+    }
+
+    #[test]
+    fn test_tasks_without_winner() {
+        let input = (vec![1, 2, 3], get_boards());
+        let no_bingo = Answer::NoSolution("no board gets bingo".to_string());
+        assert_eq!(Day4::part1(&input), no_bingo);
+        assert_eq!(Day4::part2(&input), no_bingo);
+    }
+
     #[test]
     fn test_find_last_bingo() {
         let boards = get_boards();
@@ -1,11 +1,17 @@
-use std::fs;
-use std::io::{BufRead, BufReader};
+//! Day 5: Hydrothermal Venture
+
 use std::ops::Add;
 
+use crate::grid::Grid;
+use crate::input::InputSource;
+use crate::overflow::Overflow;
+use crate::parse::{numbered_lines, parse_number, tokens, ParseError};
+use crate::solution::{Answer, Solution};
+
 #[derive(Debug, Default, Clone, Copy, PartialEq)]
-struct Vec2 {
-    x: i32,
-    y: i32,
+pub struct Vec2 {
+    pub x: i32,
+    pub y: i32,
 }
 
 impl Add for Vec2 {
@@ -20,19 +26,21 @@ impl Add for Vec2 {
 }
 
 impl Vec2 {
-    fn new(x: i32, y: i32) -> Self {
+    pub fn new(x: i32, y: i32) -> Self {
         Self { x, y }
     }
 }
 
+/// A line of hydrothermal vents, including both end points
 #[derive(Debug, Default, Clone)]
-struct Line {
-    from: Vec2,
-    to: Vec2,
+pub struct Line {
+    pub from: Vec2,
+    pub to: Vec2,
 }
 
 impl Line {
-    fn from_raw(vals: &[i32]) -> Self {
+    /// Line from `[x1, y1, x2, y2]`
+    pub fn from_raw(vals: &[i32]) -> Self {
         Self {
             from: Vec2::new(vals[0], vals[1]),
             to: Vec2::new(vals[2], vals[3]),
@@ -40,55 +48,108 @@ impl Line {
     }
 }
 
-fn read_input() -> Vec<Line> {
-    let filename = "src/day5/input";
-    let file = fs::File::open(filename).unwrap();
-    let contents = BufReader::new(file);
+fn read_input(source: &InputSource) -> Result<Vec<Line>, ParseError> {
+    let contents = source.open(5)?;
 
     let mut parsed_input: Vec<Line> = Vec::new();
-    for l in contents.lines() {
-        if let Ok(result) = l {
-            let values = result
-                .split(|c: char| !c.is_numeric())
-                .filter_map(|s| s.parse::<i32>().ok())
-                .collect::<Vec<i32>>();
-
-            if !values.is_empty() {
-                parsed_input.push(Line::from_raw(&values));
+    for line in numbered_lines(contents) {
+        let (line_number, line) = line?;
+        if !line.trim().is_empty() {
+            parsed_input.push(parse_line(&line, line_number)?);
+        }
+    }
+    if parsed_input.is_empty() {
+        return Err(ParseError::EmptyInput);
+    }
+    Ok(parsed_input)
+}
+
+// Lines are written as "x1,y1 -> x2,y2"
+fn parse_line(line: &str, line_number: usize) -> Result<Line, ParseError> {
+    const EXPECTED: [&str; 5] = ["x1", "y1", "'->'", "x2", "y2"];
+
+    let mut values = Vec::new();
+    let mut components = tokens(line, |c| c == ',' || c.is_whitespace());
+    for (idx, expected) in EXPECTED.iter().enumerate() {
+        let (column, token) = components.next().ok_or(ParseError::MissingToken {
+            line: line_number,
+            column: line.len() + 1,
+            expected,
+        })?;
+        match idx {
+            2 if token != "->" => {
+                return Err(ParseError::UnexpectedToken {
+                    line: line_number,
+                    column,
+                    token: token.to_string(),
+                    expected,
+                })
+            }
+            2 => {}
+            _ => {
+                let value = parse_number::<i32>(token, line_number, column)?;
+                if value < 0 {
+                    return Err(ParseError::UnexpectedToken {
+                        line: line_number,
+                        column,
+                        token: token.to_string(),
+                        expected: "non-negative coordinate",
+                    });
+                }
+                values.push(value);
             }
         }
     }
-    assert_eq!(parsed_input.len(), 500);
-    parsed_input
+    if let Some((column, token)) = components.next() {
+        return Err(ParseError::UnexpectedToken {
+            line: line_number,
+            column,
+            token: token.to_string(),
+            expected: "end of line",
+        });
+    }
+    Ok(Line::from_raw(&values))
 }
 
-// Assume only diagonal and axis aligned lines
-fn get_points_on_line(from: Vec2, to: Vec2) -> Vec<Vec2> {
+/// All points from `from` to `to`, assuming the line is axis aligned or diagonal
+pub fn get_points_on_line(from: Vec2, to: Vec2) -> Vec<Vec2> {
     let step = Vec2::new((to.x - from.x).signum(), (to.y - from.y).signum());
+    let n_steps = i32::max((to.x - from.x).abs(), (to.y - from.y).abs());
 
     let mut points = vec![from];
-    while points[points.len() - 1] != to {
+    for _ in 0..n_steps {
         let previous_point = points[points.len() - 1];
-        let new_point = previous_point + step;
-        points.push(new_point);
-        if points.len() > 1000 {
-            panic!("Stuck in iterations");
-        }
+        points.push(previous_point + step);
     }
     points
 }
 
-fn calculate_collision_grid(lines: &Vec<Line>, size: Vec2) -> Vec<Vec<i32>> {
-    let mut grid = vec![vec![0_i32; size.y as usize]; size.x as usize];
+/// Smallest grid that fits every line
+pub fn get_grid_size(lines: &[Line]) -> Result<Vec2, Overflow> {
+    lines.iter().try_fold(Vec2::default(), |size, l| {
+        let x = i32::max(l.from.x, l.to.x).checked_add(1);
+        let y = i32::max(l.from.y, l.to.y).checked_add(1);
+        match (x, y) {
+            (Some(x), Some(y)) => Ok(Vec2::new(i32::max(size.x, x), i32::max(size.y, y))),
+            _ => Err(Overflow("grid size")),
+        }
+    })
+}
+
+/// Number of lines covering each point, one row per `y` and one column per `x`, or an error
+/// if the grid does not fit in memory
+pub fn calculate_collision_grid(lines: &[Line], size: Vec2) -> Result<Grid<usize>, Overflow> {
+    // A point is covered at most once per line, so the counts cannot overflow
+    let mut grid = Grid::try_new(size.x as usize, size.y as usize, 0_usize)?;
     for l in lines.iter() {
         for p in get_points_on_line(l.from, l.to) {
-            grid[p.x as usize][p.y as usize] += 1;
+            grid[(p.y as usize, p.x as usize)] += 1;
         }
     }
-    grid
+    Ok(grid)
 }
 
-fn filter_to_axis_aligned(lines: &Vec<Line>) -> Vec<Line> {
+pub fn filter_to_axis_aligned(lines: &[Line]) -> Vec<Line> {
     lines
         .iter()
         .filter(|line| line.from.x == line.to.x || line.from.y == line.to.y)
@@ -96,7 +157,8 @@ fn filter_to_axis_aligned(lines: &Vec<Line>) -> Vec<Line> {
         .collect::<Vec<Line>>()
 }
 
-fn filter_to_diagonal_lines(lines: &Vec<Line>) -> Vec<Line> {
+/// Lines at 45 degrees, a single point counts as axis aligned only
+pub fn filter_to_diagonal_lines(lines: &[Line]) -> Vec<Line> {
     lines
         .iter()
         .filter(|line| {
@@ -104,14 +166,15 @@ fn filter_to_diagonal_lines(lines: &Vec<Line>) -> Vec<Line> {
             let max_x = i32::max(line.from.x, line.to.x);
             let min_y = i32::min(line.from.y, line.to.y);
             let max_y = i32::max(line.from.y, line.to.y);
-            max_x - min_x == max_y - min_y
+            max_x - min_x > 0 && max_x - min_x == max_y - min_y
         })
         .cloned()
         .collect::<Vec<Line>>()
 }
 
-fn count_collisions(grid: &Vec<Vec<i32>>, limit: i32) -> i32 {
-    grid.iter().flatten().fold(0, |acc, x| {
+/// Number of points covered by at least `limit` lines
+pub fn count_collisions(grid: &Grid<usize>, limit: usize) -> usize {
+    grid.iter().fold(0, |acc, x| {
         if x >= &limit {
             return acc + 1;
         }
@@ -119,24 +182,34 @@ fn count_collisions(grid: &Vec<Vec<i32>>, limit: i32) -> i32 {
     })
 }
 
-pub fn run() {
-    let input = read_input();
-    let mut filtered_lines = filter_to_axis_aligned(&input);
-    let collisions_grid = calculate_collision_grid(&filtered_lines, Vec2::new(1000, 1000));
-    let collisions = count_collisions(&collisions_grid, 2);
-    println!(
-        "Task1: The number of points where 2 lines overlap: {}",
-        collisions
-    );
-
-    let mut diagonal_lines = filter_to_diagonal_lines(&input);
-    filtered_lines.append(&mut diagonal_lines);
-    let collisions_grid = calculate_collision_grid(&filtered_lines, Vec2::new(1000, 1000));
-    let collisions = count_collisions(&collisions_grid, 2);
-    println!(
-        "Task2: The number of points where 2 lines overlap: {}",
-        collisions
-    );
+pub struct Day5;
+
+impl Solution for Day5 {
+    type Input = Vec<Line>;
+
+    fn parse(source: &InputSource) -> Result<Self::Input, ParseError> {
+        read_input(source)
+    }
+
+    // The number of points where at least 2 axis aligned lines overlap
+    fn part1(input: &Self::Input) -> Answer {
+        let filtered_lines = filter_to_axis_aligned(input);
+        get_grid_size(input)
+            .and_then(|size| calculate_collision_grid(&filtered_lines, size))
+            .map(|grid| count_collisions(&grid, 2))
+            .into()
+    }
+
+    // The number of points where at least 2 lines overlap, including diagonals
+    fn part2(input: &Self::Input) -> Answer {
+        let mut filtered_lines = filter_to_axis_aligned(input);
+        let mut diagonal_lines = filter_to_diagonal_lines(input);
+        filtered_lines.append(&mut diagonal_lines);
+        get_grid_size(input)
+            .and_then(|size| calculate_collision_grid(&filtered_lines, size))
+            .map(|grid| count_collisions(&grid, 2))
+            .into()
+    }
 }
 
 #[cfg(test)]
@@ -158,6 +231,26 @@ mod tests {
         ]
     }
 
+    #[test]
+    fn test_parse_line() {
+        let line = parse_line("8,0 -> 0,8", 1).unwrap();
+        assert_eq!(line.from, Vec2::new(8, 0));
+        assert_eq!(line.to, Vec2::new(0, 8));
+
+        assert_eq!(
+            parse_line("8,0 => 0,8", 2).unwrap_err().to_string(),
+            "line 2, column 5: expected '->', found '=>'"
+        );
+        assert_eq!(
+            parse_line("8,0 -> 0", 3).unwrap_err().to_string(),
+            "line 3, column 9: expected y2"
+        );
+        assert_eq!(
+            parse_line("8,a -> 0,8", 4).unwrap_err().to_string(),
+            "line 4, column 3: 'a' is not a valid number"
+        );
+    }
+
     #[test]
     fn test_filter_to_axis_aligned() {
         let inputs = get_input();
@@ -170,19 +263,19 @@ mod tests {
     fn test_calculate_collision_grid() {
         let inputs = get_input();
         let filtered = filter_to_axis_aligned(&inputs);
-        let collision_grid = calculate_collision_grid(&filtered, Vec2::new(10, 10));
-        assert_eq!(collision_grid[5][9], 1);
-        assert_eq!(collision_grid[6][9], 0);
-        assert_eq!(collision_grid[0][9], 2);
-        assert_eq!(collision_grid[1][9], 2);
-        assert_eq!(collision_grid[2][2], 1);
-        assert_eq!(collision_grid[2][1], 1);
-        assert_eq!(collision_grid[0][0], 0);
+        let collision_grid = calculate_collision_grid(&filtered, Vec2::new(10, 10)).unwrap();
+        assert_eq!(collision_grid[(9, 5)], 1);
+        assert_eq!(collision_grid[(9, 6)], 0);
+        assert_eq!(collision_grid[(9, 0)], 2);
+        assert_eq!(collision_grid[(9, 1)], 2);
+        assert_eq!(collision_grid[(2, 2)], 1);
+        assert_eq!(collision_grid[(1, 2)], 1);
+        assert_eq!(collision_grid[(0, 0)], 0);
     }
 
     #[test]
     fn test_count_collisions() {
-        let grid = vec![vec![0, 0, 2, 1, 0], vec![2, 0, 0, 1, 2]];
+        let grid = Grid::from_rows(vec![vec![0, 0, 2, 1, 0], vec![2, 0, 0, 1, 2]]);
         assert_eq!(count_collisions(&grid, 2), 3);
     }
 
@@ -199,11 +292,40 @@ mod tests {
         let mut filtered = filter_to_axis_aligned(&inputs);
         let mut diagonal_lines = filter_to_diagonal_lines(&inputs);
         filtered.append(&mut diagonal_lines);
-        let collision_grid = calculate_collision_grid(&filtered, Vec2::new(10, 10));
+        let collision_grid = calculate_collision_grid(&filtered, Vec2::new(10, 10)).unwrap();
         let count = count_collisions(&collision_grid, 2);
         assert_eq!(count, 12);
     }
 
+    #[test]
+    fn test_get_grid_size() {
+        let inputs = get_input();
+        assert_eq!(get_grid_size(&inputs), Ok(Vec2::new(10, 10)));
+        assert_eq!(
+            get_grid_size(&[Line::from_raw(&[3, 4, 1200, 4])]),
+            Ok(Vec2::new(1201, 5))
+        );
+        assert_eq!(
+            get_grid_size(&[Line::from_raw(&[3, 4, i32::MAX, 4])]),
+            Err(Overflow("grid size"))
+        );
+    }
+
+    #[test]
+    fn test_zero_length_line() {
+        let input = vec![Line::from_raw(&[1, 1, 1, 1]), Line::from_raw(&[0, 0, 2, 0])];
+        assert!(filter_to_diagonal_lines(&input).is_empty());
+        assert_eq!(Day5::part1(&input), Answer::from(0_usize));
+        assert_eq!(Day5::part2(&input), Answer::from(0_usize));
+    }
+
+    #[test]
+    fn test_grid_too_large() {
+        let input = vec![Line::from_raw(&[0, 0, 2_000_000_000, 2_000_000_000])];
+        assert_eq!(Day5::part1(&input), Answer::Overflow(Overflow("grid")));
+        assert_eq!(Day5::part2(&input), Answer::Overflow(Overflow("grid")));
+    }
+
     #[test]
     fn test_get_points_on_line() {
         let inputs = get_input();
@@ -1,8 +1,13 @@
-use std::fs;
-use std::io::Read;
+//! Day 6: Lanternfish
 
+use crate::input::InputSource;
+use crate::overflow::Overflow;
+use crate::parse::{numbered_lines, parse_number, tokens, ParseError};
+use crate::solution::{Answer, Solution};
+
+/// A single lantern fish and its reproduction timer
 #[derive(Clone, Copy)]
-struct Fish {
+pub struct Fish {
     days_left: i32, // Until reproduction
 }
 
@@ -10,10 +15,11 @@ impl Fish {
     fn new() -> Self {
         Self { days_left: 8 }
     }
-    fn from_val(val: i32) -> Self {
+    pub fn from_val(val: i32) -> Self {
         Self { days_left: val }
     }
-    fn cycle(&mut self) {
+    /// Advances the timer by a day, restarting at 6 after reproducing
+    pub fn cycle(&mut self) {
         self.days_left = match self.days_left {
             0 => 6,
             _ => self.days_left - 1,
@@ -25,7 +31,8 @@ fn get_days(fishes: &[Fish]) -> Vec<i32> {
     fishes.iter().map(|f| f.days_left).collect::<Vec<i32>>()
 }
 
-fn spawn_fish(vals: &[i32]) -> Vec<Fish> {
+/// A school of fish with the given timers
+pub fn spawn_fish(vals: &[i32]) -> Vec<Fish> {
     let mut fishes = Vec::new();
     for v in vals {
         fishes.push(Fish::from_val(*v));
@@ -33,24 +40,33 @@ fn spawn_fish(vals: &[i32]) -> Vec<Fish> {
     fishes
 }
 
-fn read_input() -> Vec<Fish> {
-    let filename = "src/day6/input";
-    let mut file = fs::File::open(filename).unwrap();
-
-    let mut contents = String::new();
-    file.read_to_string(&mut contents)
-        .expect("Failed to read input");
-    let values = contents
-        .split(',')
-        .filter_map(|r| r.parse::<i32>().ok())
-        .collect::<Vec<i32>>();
-
-    assert_eq!(values[0], 1);
-    assert_eq!(values.last().unwrap(), &1);
-    spawn_fish(&values)
+fn read_input(source: &InputSource) -> Result<Vec<Fish>, ParseError> {
+    let contents = source.open(6)?;
+
+    let mut values = Vec::new();
+    for line in numbered_lines(contents) {
+        let (line_number, line) = line?;
+        for (column, token) in tokens(&line, |c| c == ',' || c.is_whitespace()) {
+            let value = parse_number::<i32>(token, line_number, column)?;
+            if !(0..=8).contains(&value) {
+                return Err(ParseError::UnexpectedToken {
+                    line: line_number,
+                    column,
+                    token: token.to_string(),
+                    expected: "timer between 0 and 8",
+                });
+            }
+            values.push(value);
+        }
+    }
+    if values.is_empty() {
+        return Err(ParseError::EmptyInput);
+    }
+    Ok(spawn_fish(&values))
 }
 
-fn step(school: &mut Vec<Fish>) {
+/// Advances every fish by a day and adds the newborn fish to the end of the school
+pub fn step(school: &mut Vec<Fish>) {
     let mut fish_to_spawn = 0;
     for fish in school.iter_mut() {
         if fish.days_left == 0 {
@@ -62,29 +78,38 @@ fn step(school: &mut Vec<Fish>) {
     school.append(&mut new_fish);
 }
 
-fn step_n_times(school: &mut Vec<Fish>, n: usize) {
+pub fn step_n_times(school: &mut Vec<Fish>, n: usize) {
     for _ in 0..n {
         step(school);
     }
 }
 
-pub fn run() {
-    let mut school = read_input();
-    step_n_times(&mut school, 80);
-    println!(
-        "Task1: After 80 cycles there are {} lantern fish",
-        school.len()
-    );
-
-    let init_vals = get_days(&read_input());
-    println!(
-        "Task2: After 256 cycles there are {} lantern fish",
-        cycle_school_n_times(&init_vals, 256)
-    );
+pub struct Day6;
+
+impl Solution for Day6 {
+    type Input = Vec<Fish>;
+
+    fn parse(source: &InputSource) -> Result<Self::Input, ParseError> {
+        read_input(source)
+    }
+
+    // Number of lantern fish after 80 cycles
+    fn part1(school: &Self::Input) -> Answer {
+        let mut school = school.clone();
+        step_n_times(&mut school, 80);
+        school.len().into()
+    }
+
+    // Number of lantern fish after 256 cycles
+    fn part2(school: &Self::Input) -> Answer {
+        let init_vals = get_days(school);
+        cycle_school_n_times(&init_vals, 256).into()
+    }
 }
 
 // This approach is way faster
-fn spawn_schools(init_vals: &[i32]) -> Vec<u64> {
+/// Number of fish per timer value, from 0 to 8
+pub fn spawn_schools(init_vals: &[i32]) -> Vec<u64> {
     let mut fishes: Vec<u64> = vec![0; 9];
     for v in init_vals {
         fishes[*v as usize] += 1;
@@ -93,27 +118,36 @@ fn spawn_schools(init_vals: &[i32]) -> Vec<u64> {
     fishes
 }
 
-fn cycle_schools(fishes_per_day: Vec<u64>) -> Vec<u64> {
-    let mut new_fish_per_day = vec![0; 9];
+/// Advances the number of fish per timer value by a day
+pub fn cycle_schools(fishes_per_day: Vec<u64>) -> Result<Vec<u64>, Overflow> {
+    let mut new_fish_per_day = vec![0_u64; 9];
     for (idx, fish) in fishes_per_day.iter().enumerate() {
         match idx {
             0 => {
-                new_fish_per_day[6] += fish;
-                new_fish_per_day[8] += fish;
+                add_fish(&mut new_fish_per_day[6], *fish)?;
+                add_fish(&mut new_fish_per_day[8], *fish)?;
             }
-            x => new_fish_per_day[x - 1] += fish,
+            x => add_fish(&mut new_fish_per_day[x - 1], *fish)?,
         }
     }
     assert_eq!(new_fish_per_day.len(), 9);
-    new_fish_per_day
+    Ok(new_fish_per_day)
 }
 
-fn cycle_school_n_times(init_vals: &[i32], n: usize) -> u64 {
+fn add_fish(count: &mut u64, fish: u64) -> Result<(), Overflow> {
+    *count = count.checked_add(fish).ok_or(Overflow("number of fish"))?;
+    Ok(())
+}
+
+/// Total number of fish after `n` days
+pub fn cycle_school_n_times(init_vals: &[i32], n: usize) -> Result<u64, Overflow> {
     let mut schools = spawn_schools(init_vals);
     for _ in 0..n {
-        schools = cycle_schools(schools);
+        schools = cycle_schools(schools)?;
     }
-    schools.iter().fold(0, |acc, x| acc + x)
+    schools.iter().try_fold(0_u64, |total, fish| {
+        total.checked_add(*fish).ok_or(Overflow("number of fish"))
+    })
 }
 
 #[cfg(test)]
@@ -170,13 +204,23 @@ mod tests {
     fn test_cycle_school_18_times() {
         let init_vals = [3, 4, 3, 1, 2];
         let total_fish = cycle_school_n_times(&init_vals, 18);
-        assert_eq!(total_fish, 26);
+        assert_eq!(total_fish, Ok(26));
     }
 
     #[test]
     fn test_cycle_school_80_times() {
         let init_vals = [3, 4, 3, 1, 2];
         let total_fish = cycle_school_n_times(&init_vals, 80);
-        assert_eq!(total_fish, 5934);
+        assert_eq!(total_fish, Ok(5934));
+    }
+
+    #[test]
+    fn test_cycle_school_until_overflow() {
+        let init_vals = [3, 4, 3, 1, 2];
+        assert!(cycle_school_n_times(&init_vals, 256).is_ok());
+        assert_eq!(
+            cycle_school_n_times(&init_vals, 1000),
+            Err(Overflow("number of fish"))
+        );
     }
 }
@@ -1,78 +1,147 @@
-use std::{fs, io::Read};
-
-fn read_input() -> Vec<i32> {
-    let filename = "src/day7/input";
-    let mut file = fs::File::open(filename).unwrap();
-
-    let mut contents = String::new();
-    file.read_to_string(&mut contents)
-        .expect("Failed to read input");
-    let values = contents
-        .split(',')
-        .filter_map(|r| r.parse::<i32>().ok())
-        .collect::<Vec<i32>>();
-    assert_eq!(values.last().unwrap(), &947);
-    values
+//! Day 7: The Treachery of Whales
+
+use crate::input::InputSource;
+use crate::overflow::Overflow;
+use crate::parse::{numbered_lines, parse_number, tokens, ParseError};
+use crate::solution::{Answer, Solution};
+
+fn read_input(source: &InputSource) -> Result<Vec<i32>, ParseError> {
+    let contents = source.open(7)?;
+
+    let mut values = Vec::new();
+    for line in numbered_lines(contents) {
+        let (line_number, line) = line?;
+        for (column, token) in tokens(&line, |c| c == ',' || c.is_whitespace()) {
+            values.push(parse_number::<i32>(token, line_number, column)?);
+        }
+    }
+    if values.is_empty() {
+        return Err(ParseError::EmptyInput);
+    }
+    Ok(values)
 }
 
-fn get_fuel_cost_for_position_task1(crabs: &Vec<i32>, pos: i32) -> i32 {
-    crabs.iter().fold(0, |acc, c| acc + (pos - c).abs())
+/// Fuel needed to move one crab, where every step costs 1
+pub fn calculate_cost_task1(from: i32, to: i32) -> u64 {
+    from.abs_diff(to).into()
 }
 
-fn calculate_cost_task2(from: i32, to: i32) -> i32 {
-    let distance = (from - to).abs();
-    let mut cost = 0;
-    for i in 0..=distance {
-        cost += i;
-    }
-    cost
+/// Fuel needed to move one crab, where every step costs one more than the previous
+pub fn calculate_cost_task2(from: i32, to: i32) -> u64 {
+    // 1 + 2 + ... + distance, which fits as distances between i32 values are below 2^32
+    let distance = u64::from(from.abs_diff(to));
+    distance * (distance + 1) / 2
 }
 
-fn get_fuel_cost_for_position_task2(crabs: &Vec<i32>, pos: i32) -> i32 {
-    crabs
-        .iter()
-        .fold(0, |acc, c| acc + calculate_cost_task2(*c, pos))
+// Every cost fits in a u64, so no number of crabs that fits in memory can overflow the total
+fn get_total_cost(crabs: &[i32], pos: i32, cost: impl Fn(i32, i32) -> u64) -> u128 {
+    crabs.iter().map(|c| u128::from(cost(*c, pos))).sum()
 }
 
-fn find_lowest_fuel_cost_task1(crabs: &Vec<i32>) -> i32 {
-    let min = 0;
-    let max = *crabs.iter().max().unwrap();
+fn to_fuel_cost(total: u128) -> Result<u64, Overflow> {
+    u64::try_from(total).map_err(|_| Overflow("fuel cost"))
+}
 
-    let mut lowest_fuel_cost = i32::MAX;
-    for pos in min..=max {
-        let fuel_cost_for_pos = get_fuel_cost_for_position_task1(&crabs, pos);
-        if fuel_cost_for_pos < lowest_fuel_cost {
-            lowest_fuel_cost = fuel_cost_for_pos;
-        }
-    }
-    lowest_fuel_cost
+fn get_fuel_cost_for_position_task1(crabs: &[i32], pos: i32) -> Result<u64, Overflow> {
+    to_fuel_cost(get_total_cost(crabs, pos, calculate_cost_task1))
 }
 
-fn find_lowest_fuel_cost_task2(crabs: &Vec<i32>) -> i32 {
-    let min = 0;
-    let max = *crabs.iter().max().unwrap();
+/// Lowest total fuel for all crabs to align, where every step costs 1
+///
+/// Moving away from a median brings more crabs further away than closer, so a median is
+/// the best position.
+pub fn find_lowest_fuel_cost_task1(crabs: &[i32]) -> Result<u64, Overflow> {
+    let mut crabs_by_position = crabs.to_vec();
+    let middle = (crabs.len() - 1) / 2;
+    let (_, median, _) = crabs_by_position.select_nth_unstable(middle);
+    get_fuel_cost_for_position_task1(crabs, *median)
+}
 
-    let mut lowest_fuel_cost = i32::MAX;
-    for pos in min..=max {
-        let fuel_cost_for_pos = get_fuel_cost_for_position_task2(&crabs, pos);
-        if fuel_cost_for_pos < lowest_fuel_cost {
-            lowest_fuel_cost = fuel_cost_for_pos;
+/// Lowest total fuel for all crabs to align, with the cost of `calculate_cost_task2`
+///
+/// Moving one step right from `pos` changes the total by `n * (pos - mean)` plus the number
+/// of crabs at or left of `pos`, which is between 0 and `n`. The total therefore only falls
+/// towards the mean and the best position is the mean rounded either down or up.
+pub fn find_lowest_fuel_cost_task2(crabs: &[i32]) -> Result<u64, Overflow> {
+    // The sum of i32 values cannot overflow an i64 before memory runs out
+    let sum = crabs.iter().map(|c| i64::from(*c)).sum::<i64>();
+    let n = crabs.len() as i64;
+    let mean_rounded_down = sum.div_euclid(n) as i32;
+    let mean_rounded_up = -(-sum).div_euclid(n) as i32;
+    let lowest_cost = u128::min(
+        get_total_cost(crabs, mean_rounded_down, calculate_cost_task2),
+        get_total_cost(crabs, mean_rounded_up, calculate_cost_task2),
+    );
+    to_fuel_cost(lowest_cost)
+}
+
+/// Lowest total fuel for all crabs to align, for any cost that grows with the distance no
+/// slower than linearly, such as both of the puzzle's costs
+///
+/// The total is then convex in the position, so the search halves the range of positions
+/// by checking whether the cost rises or falls from one position to the next.
+pub fn find_lowest_fuel_cost_convex(
+    crabs: &[i32],
+    cost: impl Fn(i32, i32) -> u64,
+) -> Result<u64, Overflow> {
+    let (mut low, mut high) = get_range(crabs);
+    while low < high {
+        // Rounded down, so `mid + 1` is still in range
+        let mid = (i64::from(low) + i64::from(high)).div_euclid(2) as i32;
+        if get_total_cost(crabs, mid, &cost) <= get_total_cost(crabs, mid + 1, &cost) {
+            high = mid;
+        } else {
+            low = mid + 1;
         }
     }
-    lowest_fuel_cost
+    to_fuel_cost(get_total_cost(crabs, low, &cost))
 }
 
-pub fn run() {
-    let crabs = read_input();
-    let lowest_fuel_cost = find_lowest_fuel_cost_task1(&crabs);
-    println!("Task1: Lowest fuel cost to align: {}", lowest_fuel_cost);
-    let lowest_fuel_cost = find_lowest_fuel_cost_task2(&crabs);
-    println!("Task2: Lowest fuel cost to align: {}", lowest_fuel_cost)
+/// Lowest total fuel for all crabs to align, trying every position between the outermost
+/// crabs
+pub fn find_lowest_fuel_cost_brute_force(
+    crabs: &[i32],
+    cost: impl Fn(i32, i32) -> u64,
+) -> Result<u64, Overflow> {
+    let (min, max) = get_range(crabs);
+    let lowest_cost = (min..=max)
+        .map(|pos| get_total_cost(crabs, pos, &cost))
+        .min()
+        .unwrap();
+    to_fuel_cost(lowest_cost)
+}
+
+// Positions of the leftmost and rightmost crab, past which every cost only grows
+fn get_range(crabs: &[i32]) -> (i32, i32) {
+    let min = *crabs.iter().min().expect("No crabs");
+    let max = *crabs.iter().max().expect("No crabs");
+    (min, max)
+}
+
+pub struct Day7;
+
+impl Solution for Day7 {
+    type Input = Vec<i32>;
+
+    fn parse(source: &InputSource) -> Result<Self::Input, ParseError> {
+        read_input(source)
+    }
+
+    // Lowest fuel cost to align, with constant fuel rate
+    fn part1(crabs: &Self::Input) -> Answer {
+        find_lowest_fuel_cost_task1(crabs).into()
+    }
+
+    // Lowest fuel cost to align, with increasing fuel rate
+    fn part2(crabs: &Self::Input) -> Answer {
+        find_lowest_fuel_cost_task2(crabs).into()
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::testing::xorshift;
 
     fn get_inputs() -> Vec<i32> {
         vec![16, 1, 2, 0, 4, 2, 7, 1, 2, 14]
@@ -80,12 +149,12 @@ mod tests {
 
     #[test]
     fn test_get_fuel_cost_for_position_task1() {
-        assert_eq!(get_fuel_cost_for_position_task1(&get_inputs(), 2), 37);
+        assert_eq!(get_fuel_cost_for_position_task1(&get_inputs(), 2), Ok(37));
     }
 
     #[test]
     fn test_find_lowest_fuel_cost_task1() {
-        assert_eq!(find_lowest_fuel_cost_task1(&get_inputs()), 37);
+        assert_eq!(find_lowest_fuel_cost_task1(&get_inputs()), Ok(37));
     }
 
     #[test]
@@ -99,6 +168,84 @@ mod tests {
 
     #[test]
     fn test_find_lowest_fuel_cost_task2() {
-        assert_eq!(find_lowest_fuel_cost_task2(&get_inputs()), 168);
+        assert_eq!(find_lowest_fuel_cost_task2(&get_inputs()), Ok(168));
+    }
+
+    // Crabs at positions up to `range`, some of them negative
+    fn get_generated_inputs(n: usize, range: u32, seed: u64) -> Vec<i32> {
+        xorshift(seed)
+            .take(n)
+            .map(|value| (value % u64::from(range)) as i32 - range as i32 / 4)
+            .collect()
+    }
+
+    #[test]
+    fn test_searches_match_brute_force() {
+        for (n, range, seed) in [
+            (1, 10, 1),
+            (2, 10, 2),
+            (3, 5, 3),
+            (10, 100, 4),
+            (51, 2000, 5),
+        ] {
+            let mut inputs = vec![get_inputs()];
+            for seed in seed..seed + 20 {
+                inputs.push(get_generated_inputs(n, range, seed * 0x9e37_79b9));
+            }
+            for crabs in inputs.iter() {
+                let task1 = find_lowest_fuel_cost_brute_force(crabs, calculate_cost_task1);
+                let task2 = find_lowest_fuel_cost_brute_force(crabs, calculate_cost_task2);
+                assert_eq!(find_lowest_fuel_cost_task1(crabs), task1, "{:?}", crabs);
+                assert_eq!(find_lowest_fuel_cost_task2(crabs), task2, "{:?}", crabs);
+                assert_eq!(
+                    find_lowest_fuel_cost_convex(crabs, calculate_cost_task1),
+                    task1
+                );
+                assert_eq!(
+                    find_lowest_fuel_cost_convex(crabs, calculate_cost_task2),
+                    task2
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_mean_between_positions() {
+        // The mean is 4.76, the best position 4
+        let crabs = [
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 100,
+        ];
+        let brute_force = find_lowest_fuel_cost_brute_force(&crabs, calculate_cost_task2);
+        assert_eq!(find_lowest_fuel_cost_task2(&crabs), brute_force);
+    }
+
+    #[test]
+    fn test_large_fuel_costs() {
+        assert_eq!(
+            calculate_cost_task2(0, i32::MAX),
+            i32::MAX as u64 * (i32::MAX as u64 + 1) / 2
+        );
+        assert_eq!(
+            calculate_cost_task2(i32::MIN, i32::MAX),
+            9223372034707292160
+        );
+        assert_eq!(
+            to_fuel_cost(get_total_cost(
+                &[i32::MIN; 3],
+                i32::MAX,
+                calculate_cost_task2
+            )),
+            Err(Overflow("fuel cost"))
+        );
+        // Costs past a u64 on the way to the best position do not hide it
+        let crabs = [i32::MIN, i32::MIN, i32::MIN, 0, i32::MAX];
+        assert_eq!(
+            find_lowest_fuel_cost_convex(&crabs, calculate_cost_task2),
+            find_lowest_fuel_cost_task2(&crabs)
+        );
+        assert_eq!(
+            find_lowest_fuel_cost_task1(&[i32::MIN, i32::MAX]),
+            Ok(u32::MAX.into())
+        );
     }
 }
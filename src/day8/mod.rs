@@ -1,67 +1,106 @@
-use std::{fs, io::BufRead, io::BufReader};
+//! Day 8: Seven Segment Search
 
-#[derive(Debug, Default, Clone)]
-struct Pattern {
-    input: Vec<String>,
-    output: Vec<String>,
+pub mod segments;
+pub mod solver;
+
+use crate::input::InputSource;
+use crate::parse::{numbered_lines, tokens, ParseError};
+use crate::solution::{Answer, Solution};
+use segments::{Segments, N_SEGMENTS};
+
+/// The ten unique signal patterns of a display and the four digits it shows
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Pattern {
+    pub input: [Segments; 10],
+    pub output: [Segments; 4],
 }
 
-fn read_input() -> Vec<Pattern> {
-    let filename = "src/day8/input";
-    let file = fs::File::open(filename).unwrap();
-    let contents = BufReader::new(file);
-    contents
-        .lines()
-        .filter_map(|line| match line {
-            Ok(s) => parse_line(&s),
-            Err(_) => None,
-        })
-        .collect()
+fn read_input(source: &InputSource) -> Result<Vec<Pattern>, ParseError> {
+    let contents = source.open(8)?;
+    let mut patterns = Vec::new();
+    for line in numbered_lines(contents) {
+        let (line_number, line) = line?;
+        if !line.trim().is_empty() {
+            patterns.push(parse_line(&line, line_number)?);
+        }
+    }
+    if patterns.is_empty() {
+        return Err(ParseError::EmptyInput);
+    }
+    Ok(patterns)
 }
 
-fn parse_line(s: &String) -> Option<Pattern> {
-    let left = s.split('|').nth(0)?;
-    let right = s.split('|').nth(1)?;
-
-    Some(Pattern {
-        input: left
-            .split(' ')
-            .filter(|s| !s.is_empty())
-            .map(|s| String::from(s))
-            .collect(),
-        output: right
-            .split(' ')
-            .filter(|s| !s.is_empty())
-            .map(|s| String::from(s))
-            .collect(),
+// Signals and outputs are separated by a single '|'
+fn parse_line(s: &str, line: usize) -> Result<Pattern, ParseError> {
+    let (left, right) = s.split_once('|').ok_or(ParseError::MissingToken {
+        line,
+        column: s.len() + 1,
+        expected: "'|'",
+    })?;
+    if let Some(idx) = right.find('|') {
+        return Err(ParseError::UnexpectedToken {
+            line,
+            column: left.len() + idx + 2,
+            token: "|".to_string(),
+            expected: "a single '|'",
+        });
+    }
+
+    let input = parse_signals(left, line, 1)?;
+    let output = parse_signals(right, line, left.len() + 2)?;
+    Ok(Pattern {
+        input: input
+            .try_into()
+            .map_err(|input: Vec<Segments>| ParseError::WrongLength {
+                line,
+                item: "signal patterns",
+                expected: 10,
+                found: input.len(),
+            })?,
+        output: output
+            .try_into()
+            .map_err(|output: Vec<Segments>| ParseError::WrongLength {
+                line,
+                item: "output digits",
+                expected: 4,
+                found: output.len(),
+            })?,
     })
 }
 
-fn count_ones_in_output(patterns: &Vec<Pattern>) -> u32 {
+// `column` is where `s` starts on the line
+fn parse_signals(s: &str, line: usize, column: usize) -> Result<Vec<Segments>, ParseError> {
+    tokens(s, char::is_whitespace)
+        .map(|(c, token)| Segments::parse(token, line, column + c - 1))
+        .collect()
+}
+
+fn count_ones_in_output(patterns: &[Pattern]) -> usize {
     let unique_length_of_one = 2;
     count_outputs_with_length(patterns, unique_length_of_one)
 }
 
-fn count_fours_in_output(patterns: &Vec<Pattern>) -> u32 {
+fn count_fours_in_output(patterns: &[Pattern]) -> usize {
     let unique_length_of_four = 4;
     count_outputs_with_length(patterns, unique_length_of_four)
 }
 
-fn count_sevens_in_output(patterns: &Vec<Pattern>) -> u32 {
+fn count_sevens_in_output(patterns: &[Pattern]) -> usize {
     let unique_length_of_seven = 3;
     count_outputs_with_length(patterns, unique_length_of_seven)
 }
 
-fn count_eights_in_output(patterns: &Vec<Pattern>) -> u32 {
+fn count_eights_in_output(patterns: &[Pattern]) -> usize {
     let unique_length_of_eight = 7;
     count_outputs_with_length(patterns, unique_length_of_eight)
 }
 
-fn count_outputs_with_length(patterns: &Vec<Pattern>, length: usize) -> u32 {
+/// Number of output digits lighting `length` segments
+pub fn count_outputs_with_length(patterns: &[Pattern], length: usize) -> usize {
     let mut n_occurences = 0;
     for p in patterns.iter() {
         for o in p.output.iter() {
-            if o.chars().count() == length {
+            if o.len() as usize == length {
                 n_occurences += 1;
             }
         }
@@ -69,13 +108,205 @@ fn count_outputs_with_length(patterns: &Vec<Pattern>, length: usize) -> u32 {
     n_occurences
 }
 
-pub fn run() {
-    let patterns = read_input();
+// Segments lit for each digit on a working display, wires named after the segments they drive
+const DIGIT_SEGMENTS: [Segments; 10] = [
+    Segments::from_letters("abcefg"),
+    Segments::from_letters("cf"),
+    Segments::from_letters("acdeg"),
+    Segments::from_letters("acdfg"),
+    Segments::from_letters("bcdf"),
+    Segments::from_letters("abdfg"),
+    Segments::from_letters("abdefg"),
+    Segments::from_letters("acf"),
+    Segments::from_letters("abcdefg"),
+    Segments::from_letters("abcdfg"),
+];
+
+/// The segment driven by each wire, indexed by wire `a` to `g`
+pub type WireMapping = [Segments; N_SEGMENTS as usize];
 
-    let task1 = count_ones_in_output(&patterns)
-        + count_fours_in_output(&patterns)
-        + count_sevens_in_output(&patterns)
-        + count_eights_in_output(&patterns);
+// Across the ten digits segments b, e and f are lit a unique number of times. Of the two
+// segments lit eight times only c is part of 1, and of the two lit seven times only d is
+// part of 4.
+/// The wiring of a display, deduced from its ten unique signal patterns
+pub fn find_wire_mapping(signals: &[Segments]) -> Option<WireMapping> {
+    let one = signals.iter().find(|s| s.len() == 2)?;
+    let four = signals.iter().find(|s| s.len() == 4)?;
 
-    println!("Task1: 1, 4, 7 and 8 appear {} times in total", task1);
+    let mut mapping = [Segments::EMPTY; N_SEGMENTS as usize];
+    for wire in Segments::ALL.indices() {
+        let wire_set = Segments::from_index(wire);
+        let n_occurences = signals.iter().filter(|s| s.is_superset(wire_set)).count();
+        let segment = match n_occurences {
+            4 => "e",
+            6 => "b",
+            9 => "f",
+            8 if one.is_superset(wire_set) => "c",
+            8 => "a",
+            7 if four.is_superset(wire_set) => "d",
+            7 => "g",
+            _ => return None,
+        };
+        mapping[wire as usize] = Segments::from_letters(segment);
+    }
+
+    // Every signal has to show a digit with this wiring
+    match signals
+        .iter()
+        .all(|signal| decode_digit(*signal, &mapping).is_some())
+    {
+        true => Some(mapping),
+        false => None,
+    }
+}
+
+/// The digit shown by `signal` on a display wired as `mapping`
+pub fn decode_digit(signal: Segments, mapping: &WireMapping) -> Option<u32> {
+    let segments = signal.indices().fold(Segments::EMPTY, |acc, wire| {
+        acc.union(mapping[wire as usize])
+    });
+    DIGIT_SEGMENTS
+        .iter()
+        .position(|digit| *digit == segments)
+        .map(|digit| digit as u32)
+}
+
+/// The four output digits of `pattern` read as one number
+pub fn decode_output(pattern: &Pattern) -> Option<u32> {
+    let mapping = find_wire_mapping(&pattern.input)?;
+    pattern.output.iter().try_fold(0, |acc, signal| {
+        Some(acc * 10 + decode_digit(*signal, &mapping)?)
+    })
+}
+
+pub struct Day8;
+
+impl Solution for Day8 {
+    type Input = Vec<Pattern>;
+
+    fn parse(source: &InputSource) -> Result<Self::Input, ParseError> {
+        read_input(source)
+    }
+
+    // Number of times 1, 4, 7 and 8 appear in the outputs
+    fn part1(patterns: &Self::Input) -> Answer {
+        let task1 = count_ones_in_output(patterns)
+            + count_fours_in_output(patterns)
+            + count_sevens_in_output(patterns)
+            + count_eights_in_output(patterns);
+        task1.into()
+    }
+
+    // Sum of all decoded output values
+    fn part2(patterns: &Self::Input) -> Answer {
+        let mut task2 = 0_u64;
+        for (idx, pattern) in patterns.iter().enumerate() {
+            match decode_output(pattern) {
+                // Outputs are at most 9999, far too few to overflow a u64 however long the input
+                Some(output) => task2 += u64::from(output),
+                None => {
+                    return Answer::NoSolution(format!(
+                        "wiring of pattern {} cannot be deduced",
+                        idx + 1
+                    ))
+                }
+            }
+        }
+        task2.into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_patterns() -> Vec<Pattern> {
+        [
+            "be cfbegad cbdgef fgaecd cgeb fdcge agebfd fecdb fabcd edb | fdgacbe cefdb cefbgd gcbe",
+            "edbfga begcd cbg gc gcadebf fbgde acbgfd abcde gfcbed gfec | fcgedb cgb dgebacf gc",
+            "fgaebd cg bdaec gdafb agbcfd gdcbef bgcad gfac gcb cdgabef | cg cg fdcagb cbg",
+            "fbegcd cbd adcefb dageb afcb bc aefdc ecdab fgdeca fcdbega | efabcd cedba gadfec cb",
+            "aecbfdg fbg gf bafeg dbefa fcge gcbea fcaegb dgceab fcbdga | gecf egdcabf bgf bfgea",
+            "fgeab ca afcebg bdacfeg cfaedg gcfdb baec bfadeg bafgc acf | gebdcfa ecba ca fadegcb",
+            "dbcfg fgd bdegcaf fgec aegbdf ecdfab fbedc dacgb gdcebf gf | cefg dcbef fcge gbcadfe",
+            "bdfegc cbegaf gecbf dfcage bdacg ed bedf ced adcbefg gebcd | ed bcgafe cdgba cbgef",
+            "egadfb cdbfeg cegd fecab cgb gbdefca cg fgcdab egfdb bfceg | gbdfcae bgc cg cgb",
+            "gcafb gcf dcaebfg ecagb gf abcdeg gaef cafbge fdbac fegbdc | fgae cfgab fg bagce",
+        ]
+        .iter()
+        .enumerate()
+        .map(|(idx, line)| parse_line(line, idx + 1).unwrap())
+        .collect()
+    }
+
+    #[test]
+    fn test_task1() {
+        let patterns = get_patterns();
+        assert_eq!(Day8::part1(&patterns), Answer::from(26_u32));
+    }
+
+    #[test]
+    fn test_find_wire_mapping() {
+        let pattern = parse_line(
+            "acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb ab | cdfeb fcadb cdfeb cdbaf",
+            1,
+        )
+        .unwrap();
+        let mapping = find_wire_mapping(&pattern.input).unwrap();
+        let segments = mapping.iter().map(|s| s.to_string()).collect::<String>();
+        assert_eq!(segments, "cfgabde");
+        assert_eq!(
+            decode_digit(Segments::from_letters("ab"), &mapping),
+            Some(1)
+        );
+        assert_eq!(decode_output(&pattern), Some(5353));
+    }
+
+    #[test]
+    fn test_decode_output() {
+        let outputs = get_patterns()
+            .iter()
+            .map(|p| decode_output(p).unwrap())
+            .collect::<Vec<u32>>();
+        assert_eq!(
+            outputs,
+            vec![8394, 9781, 1197, 9361, 4873, 8418, 4548, 1625, 8717, 4315]
+        );
+    }
+
+    #[test]
+    fn test_task2() {
+        let patterns = get_patterns();
+        assert_eq!(Day8::part2(&patterns), Answer::from(61229_u32));
+    }
+
+    #[test]
+    fn test_parse_line() {
+        let pattern = parse_line("ab ab ab ab ab ab ab ab ab ab | ab ba ab ab", 1).unwrap();
+        assert_eq!(pattern.output[1], Segments::from_letters("ab"));
+        assert_eq!(
+            parse_line("ab ab ab ab ab ab ab ab ab ab | ab ab ax ab", 3)
+                .unwrap_err()
+                .to_string(),
+            "line 3, column 40: expected segment a to g, found 'x'"
+        );
+        assert_eq!(
+            parse_line("ab ab ab | ab ab ab ab", 1)
+                .unwrap_err()
+                .to_string(),
+            "line 1: expected 10 signal patterns, found 3"
+        );
+    }
+
+    #[test]
+    fn test_inconsistent_wiring() {
+        let pattern = parse_line("ab ab ab ab ab ab ab ab ab ab | ab ab ab ab", 1).unwrap();
+        assert_eq!(decode_output(&pattern), None);
+
+        let patterns = vec![get_patterns().remove(0), pattern];
+        assert_eq!(
+            Day8::part2(&patterns),
+            Answer::NoSolution("wiring of pattern 2 cannot be deduced".to_string())
+        );
+    }
 }
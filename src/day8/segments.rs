@@ -0,0 +1,164 @@
+//! Sets of display segments or wires, stored as the bits of a `u8`.
+
+use std::fmt;
+
+use crate::parse::ParseError;
+
+/// Number of segments of a seven segment display
+pub const N_SEGMENTS: u32 = 7;
+
+/// Most segments a set can hold, for displays other than the seven segment one
+pub const MAX_SEGMENTS: u32 = u8::BITS;
+
+/// Segments or wires `a` to `g`, bit 0 for `a` up to bit 6 for `g`, and `h` for displays with
+/// an eighth segment
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Segments(u8);
+
+impl Segments {
+    pub const EMPTY: Segments = Segments(0);
+    pub const ALL: Segments = Segments((1 << N_SEGMENTS) - 1);
+
+    /// Segments named by the letters of `s`, for constants that are known to be valid
+    pub const fn from_letters(s: &str) -> Segments {
+        let bytes = s.as_bytes();
+        let mut bits = 0;
+        let mut i = 0;
+        while i < bytes.len() {
+            bits |= 1 << (bytes[i] - b'a');
+            i += 1;
+        }
+        Segments(bits)
+    }
+
+    /// The single segment at `index`, 0 for `a`
+    pub fn from_index(index: u32) -> Segments {
+        assert!(index < MAX_SEGMENTS, "No segment {}", index);
+        Segments(1 << index)
+    }
+
+    /// The first `n` segments, all segments of a display with `n` of them
+    pub fn first(n: u32) -> Segments {
+        assert!(n <= MAX_SEGMENTS, "At most {} segments", MAX_SEGMENTS);
+        Segments(((1_u16 << n) - 1) as u8)
+    }
+
+    /// Parses a signal such as "dab", reporting the column of letters other than `a` to `g`
+    pub fn parse(token: &str, line: usize, column: usize) -> Result<Segments, ParseError> {
+        let mut segments = Segments::EMPTY;
+        for (idx, c) in token.char_indices() {
+            let segment = match c {
+                'a'..='g' => Segments::from_index(c as u32 - 'a' as u32),
+                _ => {
+                    return Err(ParseError::UnexpectedToken {
+                        line,
+                        column: column + idx,
+                        token: c.to_string(),
+                        expected: "segment a to g",
+                    })
+                }
+            };
+            if segments.is_superset(segment) {
+                return Err(ParseError::UnexpectedToken {
+                    line,
+                    column: column + idx,
+                    token: c.to_string(),
+                    expected: "every segment at most once",
+                });
+            }
+            segments = segments.union(segment);
+        }
+        Ok(segments)
+    }
+
+    pub fn bits(self) -> u8 {
+        self.0
+    }
+
+    pub fn union(self, other: Segments) -> Segments {
+        Segments(self.0 | other.0)
+    }
+
+    pub fn intersection(self, other: Segments) -> Segments {
+        Segments(self.0 & other.0)
+    }
+
+    pub fn difference(self, other: Segments) -> Segments {
+        Segments(self.0 & !other.0)
+    }
+
+    pub fn is_subset(self, other: Segments) -> bool {
+        self.intersection(other) == self
+    }
+
+    pub fn is_superset(self, other: Segments) -> bool {
+        other.is_subset(self)
+    }
+
+    /// Number of segments in the set
+    pub fn len(self) -> u32 {
+        self.0.count_ones()
+    }
+
+    pub fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    /// Indices of the segments in the set, 0 for `a`
+    pub fn indices(self) -> impl Iterator<Item = u32> {
+        (0..MAX_SEGMENTS).filter(move |idx| self.0 & (1 << idx) != 0)
+    }
+}
+
+impl fmt::Display for Segments {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for idx in self.indices() {
+            write!(f, "{}", (b'a' + idx as u8) as char)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            Segments::parse("dab", 1, 1).unwrap(),
+            Segments::from_letters("abd")
+        );
+        assert_eq!(
+            Segments::parse("dah", 2, 5).unwrap_err().to_string(),
+            "line 2, column 7: expected segment a to g, found 'h'"
+        );
+        assert_eq!(
+            Segments::parse("dad", 2, 5).unwrap_err().to_string(),
+            "line 2, column 7: expected every segment at most once, found 'd'"
+        );
+    }
+
+    #[test]
+    fn test_set_algebra() {
+        let one = Segments::from_letters("cf");
+        let seven = Segments::from_letters("acf");
+        assert_eq!(one.union(seven), seven);
+        assert_eq!(one.intersection(seven), one);
+        assert_eq!(seven.difference(one), Segments::from_letters("a"));
+        assert!(one.is_subset(seven));
+        assert!(!seven.is_subset(one));
+        assert!(seven.is_superset(one));
+        assert_eq!(seven.len(), 3);
+        assert_eq!(Segments::ALL.len(), 7);
+        assert!(Segments::EMPTY.is_empty());
+        assert_eq!(Segments::first(7), Segments::ALL);
+        assert_eq!(Segments::first(8).len(), 8);
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(Segments::from_letters("gca").to_string(), "acg");
+        assert_eq!(Segments::from_index(3).to_string(), "d");
+    }
+}
@@ -0,0 +1,392 @@
+//! Wiring of displays with any font, found by constraint propagation and backtracking.
+//!
+//! Every wire drives exactly one segment, so a wiring is a permutation of the segments. A
+//! wiring is consistent with a display when every signal it shows lights the segments of a
+//! digit of the font.
+
+use std::{error::Error, fmt};
+
+use super::segments::{Segments, MAX_SEGMENTS};
+use super::{Pattern, DIGIT_SEGMENTS};
+
+/// The segments lit for each digit, indexed by digit
+#[derive(Debug, Clone, PartialEq)]
+pub struct Font {
+    n_segments: u32,
+    digits: Vec<Segments>,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum FontError {
+    TooManySegments(u32),
+    UnknownSegment { digit: usize },
+    DuplicateDigit { first: usize, second: usize },
+}
+
+impl fmt::Display for FontError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FontError::TooManySegments(n) => write!(
+                f,
+                "a font has at most {} segments, found {}",
+                MAX_SEGMENTS, n
+            ),
+            FontError::UnknownSegment { digit } => {
+                write!(
+                    f,
+                    "digit {} lights a segment the display does not have",
+                    digit
+                )
+            }
+            FontError::DuplicateDigit { first, second } => {
+                write!(f, "digits {} and {} light the same segments", first, second)
+            }
+        }
+    }
+}
+
+impl Error for FontError {}
+
+impl Font {
+    /// A font for a display with `n_segments`, where digit `i` lights `digits[i]`
+    pub fn new(n_segments: u32, digits: Vec<Segments>) -> Result<Font, FontError> {
+        if n_segments > MAX_SEGMENTS {
+            return Err(FontError::TooManySegments(n_segments));
+        }
+        let all = Segments::first(n_segments);
+        for (digit, segments) in digits.iter().enumerate() {
+            if !segments.is_subset(all) {
+                return Err(FontError::UnknownSegment { digit });
+            }
+            if let Some(first) = digits[..digit].iter().position(|d| d == segments) {
+                return Err(FontError::DuplicateDigit {
+                    first,
+                    second: digit,
+                });
+            }
+        }
+        Ok(Font { n_segments, digits })
+    }
+
+    /// Digits 0 to 9 on a seven segment display
+    pub fn seven_segment() -> Font {
+        Font {
+            n_segments: 7,
+            digits: DIGIT_SEGMENTS.to_vec(),
+        }
+    }
+
+    pub fn n_segments(&self) -> u32 {
+        self.n_segments
+    }
+
+    /// The digit lighting exactly `segments`
+    pub fn digit(&self, segments: Segments) -> Option<usize> {
+        self.digits.iter().position(|d| *d == segments)
+    }
+}
+
+/// The segment driven by each wire, indexed by wire
+#[derive(Debug, Clone, PartialEq)]
+pub struct Wiring(Vec<u32>);
+
+impl Wiring {
+    /// Segment index driven by every wire, in wire order
+    pub fn segments(&self) -> &[u32] {
+        &self.0
+    }
+
+    /// The segments lit by `signal`
+    pub fn translate(&self, signal: Segments) -> Segments {
+        signal.indices().fold(Segments::EMPTY, |acc, wire| {
+            acc.union(Segments::from_index(self.0[wire as usize]))
+        })
+    }
+
+    /// The digit `signal` shows on `font` with this wiring
+    pub fn decode(&self, font: &Font, signal: Segments) -> Option<usize> {
+        font.digit(self.translate(signal))
+    }
+}
+
+impl fmt::Display for Wiring {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for segment in self.0.iter() {
+            write!(f, "{}", Segments::from_index(*segment))?;
+        }
+        Ok(())
+    }
+}
+
+/// Why a display has no single wiring
+#[derive(Debug, PartialEq)]
+pub enum WiringError {
+    /// No wiring shows every signal as a digit
+    Inconsistent,
+    /// More than one wiring fits, all of them are listed
+    Ambiguous(Vec<Wiring>),
+}
+
+impl fmt::Display for WiringError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            WiringError::Inconsistent => write!(f, "no wiring shows every signal as a digit"),
+            WiringError::Ambiguous(wirings) => {
+                write!(f, "{} wirings fit the signals", wirings.len())
+            }
+        }
+    }
+}
+
+impl Error for WiringError {}
+
+/// Every wiring under which all signals of `pattern`, including its outputs, show digits
+pub fn find_wirings(font: &Font, pattern: &Pattern) -> Vec<Wiring> {
+    let signals = pattern
+        .input
+        .iter()
+        .chain(pattern.output.iter())
+        .copied()
+        .collect::<Vec<Segments>>();
+    find_wirings_for_signals(font, &signals)
+}
+
+/// The only wiring of `pattern`, without guessing when there is none or several
+pub fn find_unique_wiring(font: &Font, pattern: &Pattern) -> Result<Wiring, WiringError> {
+    let mut wirings = find_wirings(font, pattern);
+    match wirings.len() {
+        0 => Err(WiringError::Inconsistent),
+        1 => Ok(wirings.remove(0)),
+        _ => Err(WiringError::Ambiguous(wirings)),
+    }
+}
+
+/// Every wiring under which all `signals` show digits of `font`
+pub fn find_wirings_for_signals(font: &Font, signals: &[Segments]) -> Vec<Wiring> {
+    let all = Segments::first(font.n_segments);
+    if signals.iter().any(|s| !s.is_subset(all)) {
+        return Vec::new();
+    }
+    // Each signal can only show the digits lighting as many segments as it has wires
+    let digits_per_signal = signals
+        .iter()
+        .map(|signal| {
+            font.digits
+                .iter()
+                .copied()
+                .filter(|d| d.len() == signal.len())
+                .collect::<Vec<Segments>>()
+        })
+        .collect::<Vec<_>>();
+
+    let candidates = match propagate(all, signals, &digits_per_signal) {
+        Some(candidates) => candidates,
+        None => return Vec::new(),
+    };
+
+    let mut search = Search {
+        signals,
+        digits_per_signal: &digits_per_signal,
+        candidates: &candidates,
+        assigned: Vec::new(),
+        used: Segments::EMPTY,
+        wirings: Vec::new(),
+    };
+    search.assign_next_wire();
+    search.wirings
+}
+
+// Narrows the segments each wire can drive: a wire of a signal drives a segment of one of
+// the digits the signal can show, and a wire outside the signal a segment outside it
+fn propagate(
+    all: Segments,
+    signals: &[Segments],
+    digits_per_signal: &[Vec<Segments>],
+) -> Option<Vec<Segments>> {
+    let mut candidates = vec![all; all.len() as usize];
+    for (signal, digits) in signals.iter().zip(digits_per_signal.iter()) {
+        let inside = digits.iter().fold(Segments::EMPTY, |acc, d| acc.union(*d));
+        let outside = digits
+            .iter()
+            .fold(Segments::EMPTY, |acc, d| acc.union(all.difference(*d)));
+        for (wire, candidate) in candidates.iter_mut().enumerate() {
+            let allowed = match signal.is_superset(Segments::from_index(wire as u32)) {
+                true => inside,
+                false => outside,
+            };
+            *candidate = candidate.intersection(allowed);
+            if candidate.is_empty() {
+                return None;
+            }
+        }
+    }
+    Some(candidates)
+}
+
+struct Search<'a> {
+    signals: &'a [Segments],
+    digits_per_signal: &'a [Vec<Segments>],
+    candidates: &'a [Segments],
+    // Segments driven by the wires assigned so far, from wire `a` on
+    assigned: Vec<u32>,
+    used: Segments,
+    wirings: Vec<Wiring>,
+}
+
+impl Search<'_> {
+    fn assign_next_wire(&mut self) {
+        let wire = self.assigned.len();
+        if wire == self.candidates.len() {
+            self.wirings.push(Wiring(self.assigned.clone()));
+            return;
+        }
+        for segment in self.candidates[wire].difference(self.used).indices() {
+            self.assigned.push(segment);
+            self.used = self.used.union(Segments::from_index(segment));
+            if self.is_consistent() {
+                self.assign_next_wire();
+            }
+            self.used = self.used.difference(Segments::from_index(segment));
+            self.assigned.pop();
+        }
+    }
+
+    // Every signal still has a digit agreeing with the wires assigned so far
+    fn is_consistent(&self) -> bool {
+        let assigned_wires = Segments::first(self.assigned.len() as u32);
+        let wiring = Wiring(self.assigned.clone());
+        self.signals
+            .iter()
+            .zip(self.digits_per_signal.iter())
+            .all(|(signal, digits)| {
+                let lit = wiring.translate(signal.intersection(assigned_wires));
+                let dark = wiring.translate(assigned_wires.difference(*signal));
+                digits
+                    .iter()
+                    .any(|d| lit.is_subset(*d) && dark.intersection(*d).is_empty())
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::day8::{find_wire_mapping, parse_line};
+
+    fn get_pattern() -> Pattern {
+        parse_line(
+            "acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb ab | cdfeb fcadb cdfeb cdbaf",
+            1,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_seven_segment_font() {
+        let font = Font::seven_segment();
+        let pattern = get_pattern();
+        let wiring = find_unique_wiring(&font, &pattern).unwrap();
+        assert_eq!(wiring.to_string(), "cfgabde");
+
+        let mapping = find_wire_mapping(&pattern.input).unwrap();
+        let expected = mapping.iter().map(|s| s.to_string()).collect::<String>();
+        assert_eq!(wiring.to_string(), expected);
+
+        let output = pattern
+            .output
+            .iter()
+            .map(|s| wiring.decode(&font, *s).unwrap())
+            .collect::<Vec<usize>>();
+        assert_eq!(output, vec![5, 3, 5, 3]);
+    }
+
+    #[test]
+    fn test_inconsistent() {
+        // Both two wire signals would have to show 1
+        let font = Font::seven_segment();
+        let pattern = parse_line("ab cd ab ab ab ab ab ab ab ab | ab ab ab ab", 1).unwrap();
+        assert_eq!(
+            find_unique_wiring(&font, &pattern),
+            Err(WiringError::Inconsistent)
+        );
+        assert_eq!(
+            WiringError::Inconsistent.to_string(),
+            "no wiring shows every signal as a digit"
+        );
+    }
+
+    #[test]
+    fn test_ambiguous() {
+        // Only 1 and 7 are shown, which leaves the wires of b, d, e and g interchangeable
+        let font = Font::seven_segment();
+        let signals = [Segments::from_letters("ab"), Segments::from_letters("abd")];
+        let wirings = find_wirings_for_signals(&font, &signals);
+        assert_eq!(wirings.len(), 2 * 24);
+        for wiring in wirings.iter() {
+            assert_eq!(wiring.decode(&font, signals[0]), Some(1));
+            assert_eq!(wiring.decode(&font, signals[1]), Some(7));
+        }
+    }
+
+    #[test]
+    fn test_custom_font() {
+        // Three segments in a row, showing 0 to 3 as the number of lit segments from the left
+        let font = Font::new(
+            3,
+            vec![
+                Segments::EMPTY,
+                Segments::from_letters("a"),
+                Segments::from_letters("ab"),
+                Segments::from_letters("abc"),
+            ],
+        )
+        .unwrap();
+        let signals = [
+            Segments::from_letters("c"),
+            Segments::from_letters("bc"),
+            Segments::from_letters("abc"),
+        ];
+        let wirings = find_wirings_for_signals(&font, &signals);
+        assert_eq!(wirings, vec![Wiring(vec![2, 1, 0])]);
+        assert_eq!(wirings[0].decode(&font, signals[1]), Some(2));
+
+        // Without the two segment signal, the wires of b and c cannot be told apart
+        let wirings = find_wirings_for_signals(&font, &[signals[0], signals[2]]);
+        assert_eq!(wirings.len(), 2);
+    }
+
+    #[test]
+    fn test_eight_segments() {
+        // A seven segment display with a decimal point, digits shown with and without it
+        let mut digits = DIGIT_SEGMENTS.to_vec();
+        digits.extend(
+            DIGIT_SEGMENTS
+                .iter()
+                .map(|d| d.union(Segments::from_letters("h"))),
+        );
+        let font = Font::new(8, digits).unwrap();
+        let pattern = get_pattern();
+        let wirings = find_wirings(&font, &pattern);
+        // The unused decimal point wire has to be h, so the wiring stays unique
+        assert_eq!(wirings.len(), 1);
+        assert_eq!(wirings[0].to_string(), "cfgabdeh");
+    }
+
+    #[test]
+    fn test_font_errors() {
+        assert_eq!(Font::new(9, Vec::new()), Err(FontError::TooManySegments(9)));
+        assert_eq!(
+            Font::new(2, vec![Segments::from_letters("c")]),
+            Err(FontError::UnknownSegment { digit: 0 })
+        );
+        assert_eq!(
+            Font::new(
+                2,
+                vec![Segments::from_letters("a"), Segments::from_letters("a")]
+            )
+            .unwrap_err()
+            .to_string(),
+            "digits 0 and 1 light the same segments"
+        );
+    }
+}
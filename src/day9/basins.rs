@@ -0,0 +1,178 @@
+//! Basins of a whole heightmap labelled in one pass with union-find.
+
+use super::BASIN_BORDER;
+use crate::grid::Grid;
+
+/// Disjoint sets of cells, merged by size and flattened while searching
+struct UnionFind {
+    parent: Vec<usize>,
+    size: Vec<usize>,
+}
+
+impl UnionFind {
+    fn new(n: usize) -> Self {
+        Self {
+            parent: (0..n).collect(),
+            size: vec![1; n],
+        }
+    }
+
+    fn find(&mut self, cell: usize) -> usize {
+        let mut root = cell;
+        while self.parent[root] != root {
+            root = self.parent[root];
+        }
+        // Point everything on the path straight at the root
+        let mut cell = cell;
+        while self.parent[cell] != root {
+            let next = self.parent[cell];
+            self.parent[cell] = root;
+            cell = next;
+        }
+        root
+    }
+
+    fn union(&mut self, a: usize, b: usize) {
+        let (a, b) = (self.find(a), self.find(b));
+        if a == b {
+            return;
+        }
+        let (larger, smaller) = match self.size[a] >= self.size[b] {
+            true => (a, b),
+            false => (b, a),
+        };
+        self.parent[smaller] = larger;
+        self.size[larger] += self.size[smaller];
+    }
+}
+
+/// Every basin of a map, points of height 9 belong to none
+#[derive(Debug, PartialEq)]
+pub struct Basins {
+    labels: Grid<Option<usize>>,
+    sizes: Vec<usize>,
+    low_points: Vec<(usize, usize)>,
+}
+
+impl Basins {
+    /// Labels the basins of a map, numbered in the order their first point appears row by row
+    pub fn label(map: &Grid<i32>) -> Self {
+        let width = map.width();
+        let cell = |(row, column): (usize, usize)| row * width + column;
+        let mut sets = UnionFind::new(width * map.height());
+        for (row, column) in map.positions() {
+            if map[(row, column)] >= BASIN_BORDER {
+                continue;
+            }
+            // Only the neighbours above and to the left have been visited yet
+            if row > 0 && map[(row - 1, column)] < BASIN_BORDER {
+                sets.union(cell((row, column)), cell((row - 1, column)));
+            }
+            if column > 0 && map[(row, column - 1)] < BASIN_BORDER {
+                sets.union(cell((row, column)), cell((row, column - 1)));
+            }
+        }
+
+        let mut label_of_root = vec![None; width * map.height()];
+        let mut labels = Grid::new(width, map.height(), None);
+        let mut sizes = Vec::new();
+        let mut low_points: Vec<(usize, usize)> = Vec::new();
+        for idx in map.positions() {
+            if map[idx] >= BASIN_BORDER {
+                continue;
+            }
+            let root = sets.find(cell(idx));
+            let label = *label_of_root[root].get_or_insert_with(|| {
+                sizes.push(sets.size[root]);
+                low_points.push(idx);
+                sizes.len() - 1
+            });
+            if map[idx] < map[low_points[label]] {
+                low_points[label] = idx;
+            }
+            labels[idx] = Some(label);
+        }
+
+        Self {
+            labels,
+            sizes,
+            low_points,
+        }
+    }
+
+    /// Number of basins
+    pub fn len(&self) -> usize {
+        self.sizes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sizes.is_empty()
+    }
+
+    /// Basin label of every point, None for points of height 9
+    pub fn labels(&self) -> &Grid<Option<usize>> {
+        &self.labels
+    }
+
+    pub fn label_at(&self, idx: (usize, usize)) -> Option<usize> {
+        self.labels[idx]
+    }
+
+    /// Number of points in every basin, indexed by label
+    pub fn sizes(&self) -> &[usize] {
+        &self.sizes
+    }
+
+    /// The lowest point of every basin, where it drains to, indexed by label
+    pub fn low_points(&self) -> &[(usize, usize)] {
+        &self.low_points
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::day9::{find_low_points, get_basin_size};
+
+    fn get_inputs() -> Grid<i32> {
+        Grid::from_rows(vec![
+            vec![2, 1, 9, 9, 9, 4, 3, 2, 1, 0],
+            vec![3, 9, 8, 7, 8, 9, 4, 9, 2, 1],
+            vec![9, 8, 5, 6, 7, 8, 9, 8, 9, 2],
+            vec![8, 7, 6, 7, 8, 9, 6, 7, 8, 9],
+            vec![9, 8, 9, 9, 9, 6, 5, 6, 7, 8],
+        ])
+    }
+
+    #[test]
+    fn test_label() {
+        let basins = Basins::label(&get_inputs());
+        assert_eq!(basins.len(), 4);
+        assert_eq!(basins.sizes(), &[3, 9, 14, 9]);
+        assert_eq!(basins.low_points(), &[(0, 1), (0, 9), (2, 2), (4, 6)]);
+        assert_eq!(basins.label_at((0, 0)), Some(0));
+        assert_eq!(basins.label_at((0, 2)), None);
+        assert_eq!(basins.label_at((4, 9)), Some(3));
+    }
+
+    #[test]
+    fn test_label_joins_basins_found_apart() {
+        // The right arm is only joined to the left one on the last row
+        let map = Grid::from_rows(vec![vec![1, 9, 0], vec![2, 9, 3], vec![4, 5, 6]]);
+        let basins = Basins::label(&map);
+        assert_eq!(basins.len(), 1);
+        assert_eq!(basins.sizes(), &[7]);
+        assert_eq!(basins.low_points(), &[(0, 2)]);
+    }
+
+    #[test]
+    fn test_matches_flood_fill() {
+        let map = get_inputs();
+        let basins = Basins::label(&map);
+        for low_point in find_low_points(&map) {
+            let label = basins.label_at(low_point).unwrap();
+            assert_eq!(basins.low_points()[label], low_point);
+            assert_eq!(basins.sizes()[label], get_basin_size(&map, low_point));
+        }
+    }
+}
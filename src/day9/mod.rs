@@ -1,93 +1,178 @@
-use std::{
-    fs,
-    io::{BufRead, BufReader},
-};
-
-fn read_input() -> Vec<Vec<i32>> {
-    let filename = "src/day9/input";
-    let file = fs::File::open(filename).unwrap();
-    let contents = BufReader::new(file);
-
-    let mut map: Vec<Vec<i32>> = Vec::new();
-    for buffer in contents.lines() {
-        if let Ok(buffer) = buffer {
-            let this_line = buffer
-                .chars()
-                .filter_map(|c| c.to_digit(10))
-                .map(|c| c as i32)
-                .collect::<Vec<i32>>();
-            map.push(this_line);
+//! Day 9: Smoke Basin
+
+pub mod basins;
+
+use crate::grid::{self, Grid};
+use crate::input::InputSource;
+use crate::overflow::Overflow;
+use crate::parse::ParseError;
+use crate::solution::{Answer, Solution};
+use basins::Basins;
+
+fn read_input(source: &InputSource) -> Result<Grid<i32>, ParseError> {
+    let contents = source.open(9)?;
+    grid::parse_digits(contents, "heights")
+}
+
+pub struct Day9;
+
+impl Solution for Day9 {
+    type Input = Grid<i32>;
+
+    fn parse(source: &InputSource) -> Result<Self::Input, ParseError> {
+        read_input(source)
+    }
+
+    // The sum of risk levels of all low points
+    fn part1(map: &Self::Input) -> Answer {
+        calculate_risk_level(&get_low_points(map)).into()
+    }
+
+    // The product of the sizes of the three largest basins
+    fn part2(map: &Self::Input) -> Answer {
+        let mut basin_sizes = Basins::label(map).sizes().to_vec();
+        if basin_sizes.len() < 3 {
+            return Answer::NoSolution(format!("3 basins are needed, found {}", basin_sizes.len()));
         }
+        basin_sizes.sort_unstable_by(|a, b| b.cmp(a));
+        basin_sizes
+            .iter()
+            .take(3)
+            .try_fold(1_usize, |product, size| product.checked_mul(*size))
+            .ok_or(Overflow("product of basin sizes"))
+            .into()
     }
-    map
 }
 
-pub fn run() {
-    let inputs = read_input();
-    let risk = calculate_risk_level(&get_low_points(&inputs));
-    println!("Task1: The risk level is {}", risk);
+/// Heights of every point lower than all its neighbours
+pub fn get_low_points(map: &Grid<i32>) -> Vec<i32> {
+    find_low_points(map).iter().map(|idx| map[*idx]).collect()
 }
 
-fn get_low_points(map: &Vec<Vec<i32>>) -> Vec<i32> {
-    let mut lows = Vec::new();
-    for x in 0..map.len() {
-        for y in 0..map[0].len() {
-            if is_low_point(&map, (x, y)) {
-                lows.push(map[x][y])
-            }
-        }
-    }
-    lows
+/// `(row, column)` of every point lower than all its neighbours
+pub fn find_low_points(map: &Grid<i32>) -> Vec<(usize, usize)> {
+    map.positions()
+        .filter(|idx| is_low_point(map, *idx))
+        .collect()
 }
 
-fn is_low_point(map: &Vec<Vec<i32>>, idx: (usize, usize)) -> bool {
-    let max_x = map.len() - 1;
-    let max_y = map[0].len() - 1;
+pub fn is_low_point(map: &Grid<i32>, idx: (usize, usize)) -> bool {
+    map.neighbours4(idx)
+        .all(|neighbour| map[neighbour] > map[idx])
+}
 
-    let mut neighbours = Vec::new();
+/// Sum of the risk levels, one more than the height, of the given points
+pub fn calculate_risk_level(heights: &[i32]) -> i64 {
+    heights.iter().fold(0, |acc, x| acc + i64::from(*x) + 1)
+}
 
-    if idx.0 > 0 {
-        neighbours.push((idx.0 - 1, idx.1));
-    }
-    if idx.0 < max_x {
-        neighbours.push((idx.0 + 1, idx.1));
-    }
-    if idx.1 > 0 {
-        neighbours.push((idx.0, idx.1 - 1));
+/// Whether neighbours of equal height can form a low point together
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LowPointMode {
+    /// Only single points lower than all their neighbours
+    Strict,
+    /// Connected points of equal height, together lower than all points around them
+    Plateau,
+}
+
+/// Connected points of the same height, lower than every point around them
+#[derive(Debug, PartialEq)]
+pub struct LowRegion {
+    pub height: i32,
+    /// `(row, column)` of the points, in the order they appear row by row
+    pub cells: Vec<(usize, usize)>,
+}
+
+impl LowRegion {
+    /// A region counts once, however many points it has
+    pub fn risk_level(&self) -> i64 {
+        i64::from(self.height) + 1
     }
-    if idx.1 < max_y {
-        neighbours.push((idx.0, idx.1 + 1));
+}
+
+/// Every low region of the map, ordered by their first point row by row
+pub fn find_low_regions(map: &Grid<i32>, mode: LowPointMode) -> Vec<LowRegion> {
+    if mode == LowPointMode::Strict {
+        return find_low_points(map)
+            .into_iter()
+            .map(|idx| LowRegion {
+                height: map[idx],
+                cells: vec![idx],
+            })
+            .collect();
     }
 
-    neighbours
-        .iter()
-        .fold(true, |acc, (x, y)| acc && (map[*x][*y] > map[idx.0][idx.1]))
+    let mut visited = Grid::new(map.width(), map.height(), false);
+    let mut regions = Vec::new();
+    for start in map.positions() {
+        if visited[start] {
+            continue;
+        }
+        let height = map[start];
+        visited[start] = true;
+        let mut to_visit = vec![start];
+        let mut cells = Vec::new();
+        let mut is_low = true;
+        while let Some(idx) = to_visit.pop() {
+            cells.push(idx);
+            for neighbour in map.neighbours4(idx) {
+                if map[neighbour] < height {
+                    is_low = false;
+                } else if map[neighbour] == height && !visited[neighbour] {
+                    visited[neighbour] = true;
+                    to_visit.push(neighbour);
+                }
+            }
+        }
+        if is_low {
+            cells.sort_unstable();
+            regions.push(LowRegion { height, cells });
+        }
+    }
+    regions
 }
 
-fn calculate_risk_level(heights: &[i32]) -> i32 {
-    heights.iter().fold(0, |acc, x| acc + x + 1)
+/// Sum of the risk levels of the given regions
+pub fn calculate_region_risk_level(regions: &[LowRegion]) -> i64 {
+    regions.iter().map(|r| r.risk_level()).sum()
 }
 
-// TODO: This calls for an iterative solution
-fn get_basin_size(map: &Vec<Vec<i32>>, start: (usize, usize)) -> i32 {
-    let directions = [(1, 1), (1, -1), (-1, 1), (-1, -1)];
-    let mut size = 0;
+/// Height that is not part of any basin
+pub const BASIN_BORDER: i32 = 9;
 
-    0
+/// Number of points in the basin around `start`, which is bounded by points of height 9
+pub fn get_basin_size(map: &Grid<i32>, start: (usize, usize)) -> usize {
+    if map[start] >= BASIN_BORDER {
+        return 0;
+    }
+    let mut visited = Grid::new(map.width(), map.height(), false);
+    visited[start] = true;
+    let mut to_visit = vec![start];
+    let mut size = 0;
+    while let Some(idx) = to_visit.pop() {
+        size += 1;
+        for neighbour in map.neighbours4(idx) {
+            if !visited[neighbour] && map[neighbour] < BASIN_BORDER {
+                visited[neighbour] = true;
+                to_visit.push(neighbour);
+            }
+        }
+    }
+    size
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    fn get_inputs() -> Vec<Vec<i32>> {
-        vec![
+    fn get_inputs() -> Grid<i32> {
+        Grid::from_rows(vec![
             vec![2, 1, 9, 9, 9, 4, 3, 2, 1, 0],
             vec![3, 9, 8, 7, 8, 9, 4, 9, 2, 1],
             vec![9, 8, 5, 6, 7, 8, 9, 8, 9, 2],
             vec![8, 7, 6, 7, 8, 9, 6, 7, 8, 9],
             vec![9, 8, 9, 9, 9, 6, 5, 6, 7, 8],
-        ]
+        ])
     }
 
     #[test]
@@ -103,11 +188,112 @@ mod tests {
     #[test]
     fn test_is_low_point() {
         let inputs = get_inputs();
-        assert_eq!(is_low_point(&inputs, (0, 1)), true);
-        assert_eq!(is_low_point(&inputs, (0, 9)), true);
-        assert_eq!(is_low_point(&inputs, (2, 2)), true);
-        assert_eq!(is_low_point(&inputs, (4, 6)), true);
-        assert_eq!(is_low_point(&inputs, (0, 0)), false);
+        assert!(is_low_point(&inputs, (0, 1)));
+        assert!(is_low_point(&inputs, (0, 9)));
+        assert!(is_low_point(&inputs, (2, 2)));
+        assert!(is_low_point(&inputs, (4, 6)));
+        assert!(!is_low_point(&inputs, (0, 0)));
+    }
+
+    #[test]
+    fn test_find_low_points() {
+        let inputs = get_inputs();
+        assert_eq!(
+            find_low_points(&inputs),
+            vec![(0, 1), (0, 9), (2, 2), (4, 6)]
+        );
+    }
+
+    #[test]
+    fn test_get_basin_size() {
+        let inputs = get_inputs();
+        assert_eq!(get_basin_size(&inputs, (0, 1)), 3);
+        assert_eq!(get_basin_size(&inputs, (2, 2)), 14);
+        assert_eq!(get_basin_size(&inputs, (0, 2)), 0);
+    }
+
+    #[test]
+    fn test_tasks() {
+        let inputs = get_inputs();
+        assert_eq!(Day9::part1(&inputs), Answer::from(15));
+        assert_eq!(Day9::part2(&inputs), Answer::from(1134_usize));
+    }
+
+    #[test]
+    fn test_task2_with_too_few_basins() {
+        let map = Grid::from_rows(vec![vec![9, 9, 9], vec![9, 9, 9]]);
+        assert_eq!(
+            Day9::part2(&map),
+            Answer::NoSolution("3 basins are needed, found 0".to_string())
+        );
+        let map = Grid::from_rows(vec![vec![1, 9, 1, 9, 1], vec![9, 9, 9, 9, 9]]);
+        assert_eq!(Day9::part2(&map), Answer::from(1_usize));
+    }
+
+    #[test]
+    fn test_task1_with_two_low_points_in_one_basin() {
+        let map = Grid::from_rows(vec![vec![1, 2, 1, 9], vec![9, 9, 9, 9]]);
+        assert_eq!(Basins::label(&map).sizes(), &[3]);
+        assert_eq!(Day9::part1(&map), Answer::from(4));
+    }
+
+    #[test]
+    fn test_low_regions_match_low_points_without_plateaus() {
+        let inputs = get_inputs();
+        for mode in [LowPointMode::Strict, LowPointMode::Plateau] {
+            let regions = find_low_regions(&inputs, mode);
+            let cells = regions.iter().map(|r| r.cells[0]).collect::<Vec<_>>();
+            assert_eq!(cells, find_low_points(&inputs));
+            assert_eq!(calculate_region_risk_level(&regions), 15);
+        }
+    }
+
+    #[test]
+    fn test_plateau_low_region() {
+        let map = Grid::from_rows(vec![
+            vec![9, 9, 9, 9, 9],
+            vec![9, 2, 2, 3, 9],
+            vec![9, 9, 2, 4, 9],
+            vec![9, 9, 9, 9, 9],
+        ]);
+        assert!(find_low_regions(&map, LowPointMode::Strict).is_empty());
+        assert_eq!(
+            find_low_regions(&map, LowPointMode::Plateau),
+            vec![LowRegion {
+                height: 2,
+                cells: vec![(1, 1), (1, 2), (2, 2)]
+            }]
+        );
+        assert_eq!(
+            calculate_region_risk_level(&find_low_regions(&map, LowPointMode::Plateau)),
+            3
+        );
+        assert_eq!(Basins::label(&map).sizes(), &[5]);
+    }
+
+    #[test]
+    fn test_plateau_next_to_lower_point() {
+        // The plateau of 1s drains into the 0 below it
+        let map = Grid::from_rows(vec![vec![1, 1, 2], vec![9, 0, 9]]);
+        let regions = find_low_regions(&map, LowPointMode::Plateau);
+        assert_eq!(
+            regions,
+            vec![LowRegion {
+                height: 0,
+                cells: vec![(1, 1)]
+            }]
+        );
+        assert_eq!(regions, find_low_regions(&map, LowPointMode::Strict));
+    }
+
+    #[test]
+    fn test_flat_map() {
+        let map = Grid::from_rows(vec![vec![5, 5], vec![5, 5]]);
+        let regions = find_low_regions(&map, LowPointMode::Plateau);
+        assert_eq!(regions.len(), 1);
+        assert_eq!(regions[0].cells.len(), 4);
+        assert_eq!(regions[0].risk_level(), 6);
+        assert_eq!(Basins::label(&map).sizes(), &[4]);
     }
 
     #[test]
@@ -0,0 +1,389 @@
+//! A two dimensional grid stored row by row in one vector.
+//!
+//! Positions are `(row, column)` pairs, counted from the top left.
+
+use std::{
+    io::BufRead,
+    ops::{Index, IndexMut},
+    str::FromStr,
+};
+
+use crate::overflow::Overflow;
+use crate::parse::{numbered_lines, parse_number, tokens, ParseError};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+// Offsets of the neighbours above, below, left and right, followed by the diagonal ones
+const NEIGHBOUR_OFFSETS: [(isize, isize); 8] = [
+    (-1, 0),
+    (1, 0),
+    (0, -1),
+    (0, 1),
+    (-1, -1),
+    (-1, 1),
+    (1, -1),
+    (1, 1),
+];
+
+impl<T> Grid<T> {
+    /// Grid of `width` columns from cells in row order
+    pub fn from_vec(width: usize, cells: Vec<T>) -> Self {
+        assert!(
+            cells.len().is_multiple_of(width),
+            "{} cells do not fill rows of {}",
+            cells.len(),
+            width
+        );
+        let height = match width {
+            0 => 0,
+            _ => cells.len() / width,
+        };
+        Self {
+            width,
+            height,
+            cells,
+        }
+    }
+
+    /// Grid from rows that all have the same length
+    pub fn from_rows(rows: Vec<Vec<T>>) -> Self {
+        let width = rows.first().map_or(0, |row| row.len());
+        assert!(
+            rows.iter().all(|row| row.len() == width),
+            "Rows differ in length"
+        );
+        Self::from_vec(width, rows.into_iter().flatten().collect())
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    fn cell_index(&self, (row, column): (usize, usize)) -> Option<usize> {
+        match row < self.height && column < self.width {
+            true => Some(row * self.width + column),
+            false => None,
+        }
+    }
+
+    /// The cell at `(row, column)`, None outside the grid
+    pub fn get(&self, position: (usize, usize)) -> Option<&T> {
+        self.cell_index(position).map(|idx| &self.cells[idx])
+    }
+
+    pub fn get_mut(&mut self, position: (usize, usize)) -> Option<&mut T> {
+        self.cell_index(position).map(|idx| &mut self.cells[idx])
+    }
+
+    pub fn row(&self, row: usize) -> &[T] {
+        &self.cells[row * self.width..(row + 1) * self.width]
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        // chunks panics on a chunk size of 0, which an empty grid would ask for
+        self.cells.chunks(self.width.max(1))
+    }
+
+    /// Cells of one column, from the top down
+    pub fn column(&self, column: usize) -> impl Iterator<Item = &T> {
+        assert!(column < self.width, "No column {}", column);
+        self.cells.iter().skip(column).step_by(self.width)
+    }
+
+    pub fn columns(&self) -> impl Iterator<Item = impl Iterator<Item = &T>> {
+        (0..self.width).map(move |column| self.column(column))
+    }
+
+    /// All cells in row order
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.cells.iter()
+    }
+
+    /// All positions in row order
+    pub fn positions(&self) -> impl Iterator<Item = (usize, usize)> {
+        let width = self.width;
+        (0..self.height).flat_map(move |row| (0..width).map(move |column| (row, column)))
+    }
+
+    /// Positions above, below, left and right of `position` that are on the grid
+    pub fn neighbours4(&self, position: (usize, usize)) -> impl Iterator<Item = (usize, usize)> {
+        self.offset_positions(position, &NEIGHBOUR_OFFSETS[..4])
+    }
+
+    /// Positions around `position`, diagonals included, that are on the grid
+    pub fn neighbours8(&self, position: (usize, usize)) -> impl Iterator<Item = (usize, usize)> {
+        self.offset_positions(position, &NEIGHBOUR_OFFSETS)
+    }
+
+    fn offset_positions(
+        &self,
+        (row, column): (usize, usize),
+        offsets: &'static [(isize, isize)],
+    ) -> impl Iterator<Item = (usize, usize)> {
+        let (width, height) = (self.width, self.height);
+        offsets.iter().filter_map(move |(dr, dc)| {
+            let row = row.checked_add_signed(*dr)?;
+            let column = column.checked_add_signed(*dc)?;
+            match row < height && column < width {
+                true => Some((row, column)),
+                false => None,
+            }
+        })
+    }
+
+    /// Grid of the same size with `f` applied to every cell
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Grid<U> {
+        Grid {
+            width: self.width,
+            height: self.height,
+            cells: self.cells.iter().map(f).collect(),
+        }
+    }
+}
+
+impl<T: Clone> Grid<T> {
+    /// Grid with every cell set to `value`
+    ///
+    /// Panics if the grid does not fit in memory, see [`Grid::try_new`] for grids of untrusted
+    /// sizes.
+    pub fn new(width: usize, height: usize, value: T) -> Self {
+        Self::try_new(width, height, value).expect("Grid is too large")
+    }
+
+    /// Grid with every cell set to `value`, or an error if it does not fit in memory
+    pub fn try_new(width: usize, height: usize, value: T) -> Result<Self, Overflow> {
+        let len = width.checked_mul(height).ok_or(Overflow("grid"))?;
+        let mut cells = Vec::new();
+        // Fails before allocating if the size in bytes does not fit an isize
+        cells.try_reserve_exact(len).map_err(|_| Overflow("grid"))?;
+        cells.resize(len, value);
+        Ok(Self {
+            width,
+            height,
+            cells,
+        })
+    }
+
+    /// Grid with rows and columns swapped
+    pub fn transpose(&self) -> Self {
+        Self {
+            width: self.height,
+            height: self.width,
+            cells: self.columns().flatten().cloned().collect(),
+        }
+    }
+}
+
+impl<T> Index<(usize, usize)> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, position: (usize, usize)) -> &T {
+        match self.cell_index(position) {
+            Some(idx) => &self.cells[idx],
+            None => panic!(
+                "Position {:?} is outside a grid of {}x{}",
+                position, self.width, self.height
+            ),
+        }
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for Grid<T> {
+    fn index_mut(&mut self, position: (usize, usize)) -> &mut T {
+        match self.cell_index(position) {
+            Some(idx) => &mut self.cells[idx],
+            None => panic!(
+                "Position {:?} is outside a grid of {}x{}",
+                position, self.width, self.height
+            ),
+        }
+    }
+}
+
+/// Grid with one digit per cell, e.g. "2199", rows of different lengths are reported as
+/// `item`
+pub fn parse_digits<T: From<u8>>(
+    reader: impl BufRead,
+    item: &'static str,
+) -> Result<Grid<T>, ParseError> {
+    parse_rows(reader, item, |line, line_number| {
+        line.char_indices()
+            .map(|(idx, c)| {
+                c.to_digit(10)
+                    .map(|d| T::from(d as u8))
+                    .ok_or(ParseError::UnexpectedToken {
+                        line: line_number,
+                        column: idx + 1,
+                        token: c.to_string(),
+                        expected: "digit",
+                    })
+            })
+            .collect()
+    })
+}
+
+/// Grid of numbers separated by whitespace, rows of different lengths are reported as `item`
+pub fn parse_whitespace_separated<T: FromStr>(
+    reader: impl BufRead,
+    item: &'static str,
+) -> Result<Grid<T>, ParseError> {
+    parse_rows(reader, item, |line, line_number| {
+        tokens(line, char::is_whitespace)
+            .map(|(column, token)| parse_number(token, line_number, column))
+            .collect()
+    })
+}
+
+// Blank lines are skipped
+fn parse_rows<T>(
+    reader: impl BufRead,
+    item: &'static str,
+    parse_row: impl Fn(&str, usize) -> Result<Vec<T>, ParseError>,
+) -> Result<Grid<T>, ParseError> {
+    let mut width = None;
+    let mut cells = Vec::new();
+    for line in numbered_lines(reader) {
+        let (line_number, line) = line?;
+        let line = line.trim_end();
+        if line.is_empty() {
+            continue;
+        }
+        let mut row = parse_row(line, line_number)?;
+        let expected = *width.get_or_insert(row.len());
+        if row.len() != expected {
+            return Err(ParseError::WrongLength {
+                line: line_number,
+                item,
+                expected,
+                found: row.len(),
+            });
+        }
+        cells.append(&mut row);
+    }
+    match width {
+        Some(width) => Ok(Grid::from_vec(width, cells)),
+        None => Err(ParseError::EmptyInput),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_grid() -> Grid<i32> {
+        Grid::from_rows(vec![vec![1, 2, 3], vec![4, 5, 6]])
+    }
+
+    #[test]
+    fn test_get() {
+        let grid = get_grid();
+        assert_eq!(grid.width(), 3);
+        assert_eq!(grid.height(), 2);
+        assert_eq!(grid.get((1, 0)), Some(&4));
+        assert_eq!(grid.get((0, 3)), None);
+        assert_eq!(grid.get((2, 0)), None);
+        assert_eq!(grid[(0, 2)], 3);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_index_outside() {
+        // In a flat vector this would wrap around to the next row
+        let _ = get_grid()[(0, 3)];
+    }
+
+    #[test]
+    fn test_rows_and_columns() {
+        let grid = get_grid();
+        assert_eq!(grid.row(1), &[4, 5, 6]);
+        assert_eq!(grid.rows().count(), 2);
+        assert_eq!(grid.column(1).copied().collect::<Vec<_>>(), vec![2, 5]);
+        assert_eq!(grid.columns().count(), 3);
+    }
+
+    #[test]
+    fn test_try_new() {
+        let grid = Grid::try_new(3, 2, 0).unwrap();
+        assert_eq!(grid, Grid::from_rows(vec![vec![0; 3]; 2]));
+        assert_eq!(Grid::try_new(usize::MAX, 2, 0), Err(Overflow("grid")));
+        assert_eq!(
+            Grid::try_new(usize::MAX / 8, 2, 0_u64),
+            Err(Overflow("grid"))
+        );
+    }
+
+    #[test]
+    fn test_transpose() {
+        let transposed = get_grid().transpose();
+        assert_eq!(
+            transposed,
+            Grid::from_rows(vec![vec![1, 4], vec![2, 5], vec![3, 6]])
+        );
+        assert_eq!(transposed.transpose(), get_grid());
+    }
+
+    #[test]
+    fn test_neighbours() {
+        let grid = get_grid();
+        let mut neighbours = grid.neighbours4((0, 0)).collect::<Vec<_>>();
+        neighbours.sort_unstable();
+        assert_eq!(neighbours, vec![(0, 1), (1, 0)]);
+
+        let mut neighbours = grid.neighbours8((0, 1)).collect::<Vec<_>>();
+        neighbours.sort_unstable();
+        assert_eq!(neighbours, vec![(0, 0), (0, 2), (1, 0), (1, 1), (1, 2)]);
+    }
+
+    #[test]
+    fn test_positions_and_map() {
+        let grid = get_grid();
+        let positions = grid.positions().collect::<Vec<_>>();
+        assert_eq!(positions[3], (1, 0));
+        assert_eq!(grid.map(|v| v * 2)[(1, 2)], 12);
+    }
+
+    #[test]
+    fn test_parse_digits() {
+        let grid = parse_digits::<i32>("219\n398\n\n".as_bytes(), "digits").unwrap();
+        assert_eq!(grid, Grid::from_rows(vec![vec![2, 1, 9], vec![3, 9, 8]]));
+        assert_eq!(
+            parse_digits::<i32>("219\n39\n".as_bytes(), "digits")
+                .unwrap_err()
+                .to_string(),
+            "line 2: expected 3 digits, found 2"
+        );
+        assert_eq!(
+            parse_digits::<i32>("2x9\n".as_bytes(), "digits")
+                .unwrap_err()
+                .to_string(),
+            "line 1, column 2: expected digit, found 'x'"
+        );
+        assert!(parse_digits::<i32>("\n".as_bytes(), "digits").is_err());
+    }
+
+    #[test]
+    fn test_parse_whitespace_separated() {
+        let grid =
+            parse_whitespace_separated::<u8>("22 13\n 8  2\n".as_bytes(), "numbers").unwrap();
+        assert_eq!(grid, Grid::from_rows(vec![vec![22, 13], vec![8, 2]]));
+        assert_eq!(
+            parse_whitespace_separated::<u8>("22 13\n8 x\n".as_bytes(), "numbers")
+                .unwrap_err()
+                .to_string(),
+            "line 2, column 3: 'x' is not a valid number"
+        );
+    }
+}
@@ -0,0 +1,131 @@
+//! Where puzzle inputs are read from.
+
+use std::{
+    env, fs,
+    io::{self, BufRead, BufReader},
+    path::PathBuf,
+};
+
+/// Environment variable naming a directory laid out like `src`, with one `day<N>/input` per day
+pub const INPUT_DIR_VARIABLE: &str = "AOC_INPUT_DIR";
+
+// Inputs in a directory follow the same layout as this repo, <dir>/day<N>/input,
+// so pointing AOC_INPUT_DIR at a checkout's src directory works out of the box
+const DEFAULT_INPUT_DIR: &str = "src";
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum InputSource {
+    /// `<dir>/day<N>/input` for every day
+    Dir(PathBuf),
+    /// The same file whatever the day
+    File(PathBuf),
+    Stdin,
+}
+
+impl Default for InputSource {
+    fn default() -> Self {
+        InputSource::Dir(PathBuf::from(DEFAULT_INPUT_DIR))
+    }
+}
+
+impl InputSource {
+    /// Path given on the command line, where "-" means stdin
+    pub fn from_arg(arg: &str) -> Self {
+        match arg {
+            "-" => InputSource::Stdin,
+            path => InputSource::File(PathBuf::from(path)),
+        }
+    }
+
+    /// The directory in `AOC_INPUT_DIR`, or `src` if it is not set
+    pub fn from_env() -> Self {
+        match env::var_os(INPUT_DIR_VARIABLE) {
+            Some(dir) => InputSource::Dir(PathBuf::from(dir)),
+            None => InputSource::default(),
+        }
+    }
+
+    /// The file to read for `day`, or None for stdin
+    pub fn path(&self, day: u8) -> Option<PathBuf> {
+        match self {
+            InputSource::Dir(dir) => Some(dir.join(format!("day{}", day)).join("input")),
+            InputSource::File(path) => Some(path.clone()),
+            InputSource::Stdin => None,
+        }
+    }
+
+    /// Identifies the input in reports, "-" for stdin
+    pub fn name(&self, day: u8) -> String {
+        match self.path(day) {
+            Some(path) => path.display().to_string(),
+            None => "-".to_string(),
+        }
+    }
+
+    /// Expected answers are only kept next to inputs in a directory
+    pub fn answers_path(&self, day: u8) -> Option<PathBuf> {
+        match self {
+            InputSource::Dir(dir) => Some(dir.join(format!("day{}", day)).join("answers")),
+            _ => None,
+        }
+    }
+
+    /// Opens the input of `day` for reading
+    pub fn open(&self, day: u8) -> io::Result<Box<dyn BufRead>> {
+        match self.path(day) {
+            Some(path) => {
+                let file = fs::File::open(&path)
+                    .map_err(|e| io::Error::new(e.kind(), format!("{}: {}", path.display(), e)))?;
+                Ok(Box::new(BufReader::new(file)))
+            }
+            None => Ok(Box::new(BufReader::new(io::stdin()))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_path_in_dir() {
+        let source = InputSource::Dir(PathBuf::from("inputs"));
+        assert_eq!(source.path(4), Some(PathBuf::from("inputs/day4/input")));
+        assert_eq!(source.name(4), "inputs/day4/input");
+        assert_eq!(InputSource::Stdin.name(4), "-");
+    }
+
+    #[test]
+    fn test_answers_path() {
+        let source = InputSource::Dir(PathBuf::from("inputs"));
+        assert_eq!(
+            source.answers_path(4),
+            Some(PathBuf::from("inputs/day4/answers"))
+        );
+        assert_eq!(InputSource::Stdin.answers_path(4), None);
+    }
+
+    #[test]
+    fn test_from_arg() {
+        assert_eq!(InputSource::from_arg("-"), InputSource::Stdin);
+        assert_eq!(
+            InputSource::from_arg("mine.txt"),
+            InputSource::File(PathBuf::from("mine.txt"))
+        );
+        assert_eq!(
+            InputSource::from_arg("mine.txt").path(9),
+            Some(PathBuf::from("mine.txt"))
+        );
+    }
+
+    #[test]
+    fn test_open_default_input() {
+        let mut first_line = String::new();
+        InputSource::default()
+            .open(1)
+            .unwrap()
+            .read_line(&mut first_line)
+            .unwrap();
+        assert!(first_line.trim().parse::<i32>().is_ok());
+    }
+}
@@ -0,0 +1,25 @@
+//! Solutions to Advent of Code 2021.
+//!
+//! Every day lives in its own module and implements [`solution::Solution`]. The
+//! [`solution::DAYS`] registry erases the input types so all days can be run
+//! from one list, which is what the `aoc2021` binary does.
+
+pub mod bench;
+pub mod day1;
+pub mod day2;
+pub mod day3;
+pub mod day4;
+pub mod day5;
+pub mod day6;
+pub mod day7;
+pub mod day8;
+pub mod day9;
+pub mod grid;
+pub mod input;
+pub mod overflow;
+pub mod parse;
+pub mod report;
+pub mod solution;
+#[cfg(test)]
+mod testing;
+pub mod verify;
@@ -1,29 +1,259 @@
-use std::env;
-
-mod day1;
-mod day2;
-mod day3;
-mod day4;
-mod day5;
-mod day6;
-mod day7;
-mod day8;
-mod day9;
+use std::{env, path::Path, process};
+
+mod cli;
+
+use aoc2021::bench::{self, Baseline, BenchConfig};
+use aoc2021::input::InputSource;
+use aoc2021::report::{self, Format};
+use aoc2021::solution::{self, Day, Part, DAYS};
+use aoc2021::verify::{self, Verdict};
+use cli::Command;
 
 fn main() {
-    let args: Vec<String> = env::args().collect();
-    assert!(args.len() > 0, "Add day as input argument");
-
-    match args[1].as_str() {
-        "1" => day1::run(),
-        "2" => day2::run(),
-        "3" => day3::run(),
-        "4" => day4::run(),
-        "5" => day5::run(),
-        "6" => day6::run(),
-        "7" => day7::run(),
-        "8" => day8::run(),
-        "9" => day9::run(),
-        _ => {}
+    let args: Vec<String> = env::args().skip(1).collect();
+    let command = match cli::parse_args(&args) {
+        Ok(command) => command,
+        Err(e) => exit_with_usage(&e.to_string()),
+    };
+
+    match command {
+        Command::Run {
+            day,
+            part,
+            input,
+            format,
+        } => {
+            let day = match solution::find_day(day) {
+                Some(day) => day,
+                None => exit_with_usage(&format!("no solution registered for day {}", day)),
+            };
+            let parts = match part {
+                Some(part) => vec![part],
+                None => Part::ALL.to_vec(),
+            };
+            let source = input.unwrap_or_else(InputSource::from_env);
+            if !print_answers(day, &source, &parts, format) {
+                process::exit(1);
+            }
+        }
+        Command::All { format } => {
+            let source = InputSource::from_env();
+            let mut reports = Vec::new();
+            let mut all_succeeded = true;
+            for day in DAYS.iter() {
+                match day.solve(&source, &Part::ALL) {
+                    Ok(report) => reports.push(report),
+                    Err(e) => {
+                        eprintln!("error: day {}: {}", day.number, e);
+                        all_succeeded = false;
+                    }
+                }
+            }
+            match format {
+                Format::Text => print!("{}", report::format_table(&reports)),
+                Format::Json => {
+                    for r in reports.iter() {
+                        print!("{}", report::format_json(r, &source.name(r.day)));
+                    }
+                }
+            }
+            for r in reports.iter() {
+                for part in r.parts.iter() {
+                    if let Some(e) = part.answer.error() {
+                        eprintln!("error: day {} part {}: {}", r.day, part.part, e);
+                        all_succeeded = false;
+                    }
+                }
+            }
+            if !all_succeeded {
+                process::exit(1);
+            }
+        }
+        Command::Verify { day } => {
+            let days = match day {
+                Some(number) => match solution::find_day(number) {
+                    Some(day) => vec![day],
+                    None => exit_with_usage(&format!("no solution registered for day {}", number)),
+                },
+                None => DAYS.iter().collect(),
+            };
+            if !verify_answers(&days, &InputSource::from_env()) {
+                process::exit(1);
+            }
+        }
+        Command::Bench {
+            day,
+            part,
+            input,
+            config,
+            baseline,
+            save,
+            threshold,
+        } => {
+            let day = match solution::find_day(day) {
+                Some(day) => day,
+                None => exit_with_usage(&format!("no solution registered for day {}", day)),
+            };
+            let parts = match part {
+                Some(part) => vec![part],
+                None => Part::ALL.to_vec(),
+            };
+            let source = input.unwrap_or_else(InputSource::from_env);
+            let options = BenchOptions {
+                config,
+                baseline: baseline.as_deref(),
+                save: save.as_deref(),
+                threshold,
+            };
+            if !run_bench(day, &source, &parts, &options) {
+                process::exit(1);
+            }
+        }
+        Command::List => {
+            for day in DAYS.iter() {
+                println!("{:>2}  {}", day.number, day.title);
+            }
+        }
+        Command::Help => println!("{}", cli::usage()),
+    }
+}
+
+// Returns false if the input could not be parsed or an answer is an error
+fn print_answers(day: &Day, source: &InputSource, parts: &[Part], format: Format) -> bool {
+    match day.solve(source, parts) {
+        Ok(report) if format == Format::Json => {
+            print!("{}", report::format_json(&report, &source.name(day.number)));
+            !report::has_errors([&report])
+        }
+        Ok(report) => {
+            for part in report.parts.iter() {
+                match part.answer.error() {
+                    Some(e) => eprintln!("error: day {} part {}: {}", day.number, part.part, e),
+                    None => println!("Day {} part {}: {}", day.number, part.part, part.answer),
+                }
+            }
+            !report::has_errors([&report])
+        }
+        Err(e) => {
+            eprintln!("error: day {}: {}", day.number, e);
+            false
+        }
     }
 }
+
+// Returns false if any answer did not match or a day could not be solved
+fn verify_answers(days: &[&Day], source: &InputSource) -> bool {
+    let (mut passed, mut failed, mut missing) = (0, 0, 0);
+    let mut all_succeeded = true;
+    for day in days {
+        let result = verify::read_expected(source, day.number)
+            .and_then(|expected| Ok((expected, day.solve(source, &Part::ALL)?)));
+        let (expected, report) = match result {
+            Ok(result) => result,
+            Err(e) => {
+                eprintln!("error: day {}: {}", day.number, e);
+                all_succeeded = false;
+                continue;
+            }
+        };
+        for part in report.parts.iter() {
+            let verdict = match verify::check(&expected, part) {
+                Verdict::Pass => {
+                    passed += 1;
+                    "pass".to_string()
+                }
+                Verdict::Fail { expected } => {
+                    failed += 1;
+                    format!("FAIL, expected {}", expected)
+                }
+                Verdict::Missing => {
+                    missing += 1;
+                    "missing".to_string()
+                }
+            };
+            println!(
+                "Day {} part {}: {} ({})",
+                day.number, part.part, verdict, part.answer
+            );
+        }
+    }
+    println!("{} passed, {} failed, {} missing", passed, failed, missing);
+    all_succeeded && failed == 0
+}
+
+struct BenchOptions<'a> {
+    config: BenchConfig,
+    baseline: Option<&'a Path>,
+    save: Option<&'a Path>,
+    threshold: f64,
+}
+
+// Returns false if the input or a baseline could not be read, or a part regressed
+fn run_bench(day: &Day, source: &InputSource, parts: &[Part], options: &BenchOptions) -> bool {
+    let baseline = match options.baseline.map(Baseline::read).transpose() {
+        Ok(baseline) => baseline,
+        Err(e) => {
+            eprintln!("error: baseline: {}", e);
+            return false;
+        }
+    };
+    let results = match day.bench(source, parts, &options.config) {
+        Ok(results) => results,
+        Err(e) => {
+            eprintln!("error: day {}: {}", day.number, e);
+            return false;
+        }
+    };
+
+    let comparisons = match baseline {
+        Some(baseline) => bench::compare(day.number, &results, &baseline, options.threshold),
+        None => Vec::new(),
+    };
+    for (part, stats) in results.iter() {
+        let mut line = format!(
+            "Day {} part {}: mean {}, median {}, min {}, stddev {} ({} runs)",
+            day.number,
+            part,
+            report::format_duration(stats.mean),
+            report::format_duration(stats.median),
+            report::format_duration(stats.min),
+            report::format_duration(stats.stddev),
+            stats.iterations
+        );
+        if let Some(comparison) = comparisons.iter().find(|c| c.part == *part) {
+            let baseline = report::format_duration(comparison.baseline);
+            match comparison.change {
+                Some(change) => {
+                    line.push_str(&format!(", {:+.1}% vs baseline {}", change, baseline))
+                }
+                None => line.push_str(&format!(", vs baseline {}", baseline)),
+            }
+            if comparison.regressed {
+                line.push_str(" REGRESSION");
+            }
+        }
+        println!("{}", line);
+    }
+
+    if let Some(path) = options.save {
+        // Entries of other days and parts already in the file are kept
+        let saved = Baseline::read(path).map(|mut saved| {
+            saved.merge(day.number, &results);
+            saved
+        });
+        let written = match saved {
+            Ok(saved) => saved.write(path).map_err(|e| e.to_string()),
+            Err(e) => Err(e.to_string()),
+        };
+        if let Err(e) = written {
+            eprintln!("error: {}: {}", path.display(), e);
+            return false;
+        }
+    }
+    !comparisons.iter().any(|c| c.regressed)
+}
+
+fn exit_with_usage(message: &str) -> ! {
+    eprintln!("error: {}\n\n{}", message, cli::usage());
+    process::exit(2);
+}
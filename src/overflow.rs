@@ -0,0 +1,16 @@
+//! The error for results too large for the integer type they are computed in.
+
+use std::error::Error;
+use std::fmt;
+
+/// A computation whose result would not fit, naming the value being computed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Overflow(pub &'static str);
+
+impl fmt::Display for Overflow {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} is too large to compute", self.0)
+    }
+}
+
+impl Error for Overflow {}
@@ -0,0 +1,184 @@
+//! Errors and helpers shared by the parsers of every day.
+
+use std::{
+    error::Error,
+    fmt,
+    io::{self, BufRead},
+    str::FromStr,
+};
+
+/// Lines and columns are counted from 1, like in an editor
+#[derive(Debug)]
+pub enum ParseError {
+    Io(io::Error),
+    EmptyInput,
+    InvalidNumber {
+        line: usize,
+        column: usize,
+        token: String,
+    },
+    UnexpectedToken {
+        line: usize,
+        column: usize,
+        token: String,
+        expected: &'static str,
+    },
+    MissingToken {
+        line: usize,
+        column: usize,
+        expected: &'static str,
+    },
+    WrongLength {
+        line: usize,
+        item: &'static str,
+        expected: usize,
+        found: usize,
+    },
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::Io(e) => write!(f, "failed to read input: {}", e),
+            ParseError::EmptyInput => write!(f, "input is empty"),
+            ParseError::InvalidNumber {
+                line,
+                column,
+                token,
+            } => write!(
+                f,
+                "line {}, column {}: '{}' is not a valid number",
+                line, column, token
+            ),
+            ParseError::UnexpectedToken {
+                line,
+                column,
+                token,
+                expected,
+            } => write!(
+                f,
+                "line {}, column {}: expected {}, found '{}'",
+                line, column, expected, token
+            ),
+            ParseError::MissingToken {
+                line,
+                column,
+                expected,
+            } => write!(f, "line {}, column {}: expected {}", line, column, expected),
+            ParseError::WrongLength {
+                line,
+                item,
+                expected,
+                found,
+            } => write!(
+                f,
+                "line {}: expected {} {}, found {}",
+                line, expected, item, found
+            ),
+        }
+    }
+}
+
+impl Error for ParseError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ParseError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for ParseError {
+    fn from(e: io::Error) -> Self {
+        ParseError::Io(e)
+    }
+}
+
+/// Lines of the input together with their line number
+pub fn numbered_lines(
+    reader: impl BufRead,
+) -> impl Iterator<Item = Result<(usize, String), ParseError>> {
+    reader
+        .lines()
+        .enumerate()
+        .map(|(idx, line)| Ok((idx + 1, line?)))
+}
+
+/// Non-empty tokens between separators, together with the column each token starts at
+pub fn tokens(
+    line: &str,
+    is_separator: impl Fn(char) -> bool,
+) -> impl Iterator<Item = (usize, &str)> {
+    line.split(is_separator)
+        .filter(|token| !token.is_empty())
+        .map(move |token| (token.as_ptr() as usize - line.as_ptr() as usize + 1, token))
+}
+
+/// The only token on a line, or None if the line is blank
+pub fn single_token(line: &str, line_number: usize) -> Result<Option<(usize, &str)>, ParseError> {
+    let mut tokens = tokens(line, char::is_whitespace);
+    let first = tokens.next();
+    match tokens.next() {
+        Some((column, token)) => Err(ParseError::UnexpectedToken {
+            line: line_number,
+            column,
+            token: token.to_string(),
+            expected: "end of line",
+        }),
+        None => Ok(first),
+    }
+}
+
+/// Parses a token, reporting where it was found if it is not a number
+pub fn parse_number<T: FromStr>(token: &str, line: usize, column: usize) -> Result<T, ParseError> {
+    token.parse::<T>().map_err(|_| ParseError::InvalidNumber {
+        line,
+        column,
+        token: token.to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tokens_have_columns() {
+        let found = tokens(" 22 13  17", char::is_whitespace).collect::<Vec<_>>();
+        assert_eq!(found, vec![(2, "22"), (5, "13"), (9, "17")]);
+
+        let found = tokens("3,4,,1,", |c| c == ',').collect::<Vec<_>>();
+        assert_eq!(found, vec![(1, "3"), (3, "4"), (6, "1")]);
+    }
+
+    #[test]
+    fn test_numbered_lines() {
+        let lines = numbered_lines("a\n\nb\n".as_bytes())
+            .map(|l| l.unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            lines,
+            vec![
+                (1, "a".to_string()),
+                (2, "".to_string()),
+                (3, "b".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn test_single_token() {
+        assert_eq!(single_token("  199", 1).unwrap(), Some((3, "199")));
+        assert_eq!(single_token("", 1).unwrap(), None);
+        assert!(single_token("199 200", 1).is_err());
+    }
+
+    #[test]
+    fn test_parse_number_error_message() {
+        let error = parse_number::<i32>("2x", 4, 7).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "line 4, column 7: '2x' is not a valid number"
+        );
+    }
+}
@@ -0,0 +1,217 @@
+//! Formatting of answers and timings for humans and other tools.
+
+use std::time::Duration;
+
+use crate::solution::{Answer, Report};
+
+/// Output format of the run and all commands
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+    Text,
+    Json,
+}
+
+/// Duration with a unit suited to its size, e.g. "2.50 ms"
+pub fn format_duration(duration: Duration) -> String {
+    let micros = duration.as_secs_f64() * 1e6;
+    if micros < 1e3 {
+        format!("{:.1} µs", micros)
+    } else if micros < 1e6 {
+        format!("{:.2} ms", micros / 1e3)
+    } else {
+        format!("{:.2} s", micros / 1e6)
+    }
+}
+
+/// One row per parse and part, followed by the total time of all reports
+pub fn format_table(reports: &[Report]) -> String {
+    let mut rows = vec![[
+        "Day".to_string(),
+        "Part".to_string(),
+        "Answer".to_string(),
+        "Time".to_string(),
+    ]];
+    for report in reports {
+        rows.push([
+            report.day.to_string(),
+            "parse".to_string(),
+            String::new(),
+            format_duration(report.parse_duration),
+        ]);
+        for part in report.parts.iter() {
+            rows.push([
+                report.day.to_string(),
+                part.part.to_string(),
+                part.answer.to_string(),
+                format_duration(part.duration),
+            ]);
+        }
+    }
+    let total = reports.iter().map(|r| r.total_duration()).sum::<Duration>();
+    rows.push([
+        "Total".to_string(),
+        String::new(),
+        String::new(),
+        format_duration(total),
+    ]);
+
+    let mut widths = [0; 4];
+    for row in rows.iter() {
+        for (width, cell) in widths.iter_mut().zip(row.iter()) {
+            *width = usize::max(*width, cell.chars().count());
+        }
+    }
+
+    let mut table = String::new();
+    for row in rows.iter() {
+        let line = format!(
+            "{:>w0$}  {:<w1$}  {:<w2$}  {:>w3$}",
+            row[0],
+            row[1],
+            row[2],
+            row[3],
+            w0 = widths[0],
+            w1 = widths[1],
+            w2 = widths[2],
+            w3 = widths[3],
+        );
+        table.push_str(line.trim_end());
+        table.push('\n');
+    }
+    table
+}
+
+/// One JSON object per line and part, so reports can be streamed into other tools
+///
+/// Answers that are errors are `null` with an extra `"error"` field, which unsolved parts
+/// do not have.
+pub fn format_json(report: &Report, input: &str) -> String {
+    let mut lines = String::new();
+    for part in report.parts.iter() {
+        let error = match part.answer.error() {
+            Some(e) => format!(",\"error\":{}", string_to_json(&e)),
+            None => String::new(),
+        };
+        lines.push_str(&format!(
+            "{{\"day\":{},\"part\":{},\"answer\":{}{},\"duration_ns\":{},\"parse_duration_ns\":{},\"input\":{}}}\n",
+            report.day,
+            part.part,
+            answer_to_json(&part.answer),
+            error,
+            part.duration.as_nanos(),
+            report.parse_duration.as_nanos(),
+            string_to_json(input)
+        ));
+    }
+    lines
+}
+
+fn answer_to_json(answer: &Answer) -> String {
+    match answer {
+        Answer::Signed(v) => v.to_string(),
+        Answer::Unsigned(v) => v.to_string(),
+        Answer::Unsolved | Answer::Overflow(_) | Answer::NoSolution(_) => "null".to_string(),
+    }
+}
+
+/// Whether any answer of the reports is an error
+pub fn has_errors<'a>(reports: impl IntoIterator<Item = &'a Report>) -> bool {
+    reports
+        .into_iter()
+        .flat_map(|report| report.parts.iter())
+        .any(|part| part.answer.error().is_some())
+}
+
+fn string_to_json(s: &str) -> String {
+    let mut escaped = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\t' => escaped.push_str("\\t"),
+            c if c.is_control() => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::overflow::Overflow;
+    use crate::solution::{Answer, Part, PartReport};
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(Duration::from_nanos(1500)), "1.5 µs");
+        assert_eq!(format_duration(Duration::from_micros(2500)), "2.50 ms");
+        assert_eq!(format_duration(Duration::from_millis(3250)), "3.25 s");
+    }
+
+    fn get_report() -> Report {
+        Report {
+            day: 7,
+            parse_duration: Duration::from_micros(10),
+            parts: vec![
+                PartReport {
+                    part: Part::One,
+                    answer: Answer::from(37),
+                    duration: Duration::from_micros(20),
+                },
+                PartReport {
+                    part: Part::Two,
+                    answer: Answer::from(168),
+                    duration: Duration::from_millis(1),
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn test_format_table() {
+        let reports = vec![get_report()];
+        let expected = [
+            "  Day  Part   Answer     Time",
+            "    7  parse          10.0 µs",
+            "    7  1      37      20.0 µs",
+            "    7  2      168     1.00 ms",
+            "Total                 1.03 ms",
+        ];
+        assert_eq!(format_table(&reports), expected.join("\n") + "\n");
+    }
+
+    #[test]
+    fn test_format_json() {
+        let mut report = get_report();
+        report.parts[1].answer = Answer::Unsolved;
+        let expected = [
+            r#"{"day":7,"part":1,"answer":37,"duration_ns":20000,"parse_duration_ns":10000,"input":"src/day7/input"}"#,
+            r#"{"day":7,"part":2,"answer":null,"duration_ns":1000000,"parse_duration_ns":10000,"input":"src/day7/input"}"#,
+        ];
+        assert_eq!(
+            format_json(&report, "src/day7/input"),
+            expected.join("\n") + "\n"
+        );
+    }
+
+    #[test]
+    fn test_format_json_error() {
+        let mut report = get_report();
+        report.parts[1].answer = Answer::Overflow(Overflow("fuel cost"));
+        let expected = [
+            r#"{"day":7,"part":1,"answer":37,"duration_ns":20000,"parse_duration_ns":10000,"input":"-"}"#,
+            r#"{"day":7,"part":2,"answer":null,"error":"fuel cost is too large to compute","duration_ns":1000000,"parse_duration_ns":10000,"input":"-"}"#,
+        ];
+        assert_eq!(format_json(&report, "-"), expected.join("\n") + "\n");
+        assert!(has_errors([&report]));
+        assert!(!has_errors([&get_report()]));
+    }
+
+    #[test]
+    fn test_string_to_json() {
+        assert_eq!(string_to_json("a\"b\\c\n"), r#""a\"b\\c\n""#);
+    }
+}
@@ -0,0 +1,285 @@
+//! The common interface of all days and the registry that lists them.
+
+use std::fmt;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use crate::bench::{self, BenchConfig, Stats};
+use crate::input::InputSource;
+use crate::overflow::Overflow;
+use crate::parse::ParseError;
+use crate::{day1, day2, day3, day4, day5, day6, day7, day8, day9};
+
+/// The answer to one part of a puzzle
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Answer {
+    Signed(i64),
+    /// Wide enough for the product of two 64 bit values
+    Unsigned(u128),
+    /// The part has no solution yet
+    Unsolved,
+    /// The answer does not fit the type it is computed in
+    Overflow(Overflow),
+    /// The input is well formed but has no answer, for the given reason
+    NoSolution(String),
+}
+
+impl fmt::Display for Answer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Answer::Signed(v) => write!(f, "{}", v),
+            Answer::Unsigned(v) => write!(f, "{}", v),
+            Answer::Unsolved => write!(f, "unsolved"),
+            Answer::Overflow(e) => write!(f, "overflow ({})", e.0),
+            Answer::NoSolution(reason) => write!(f, "no solution ({})", reason),
+        }
+    }
+}
+
+impl Answer {
+    /// Why no answer could be given, for answers that are errors
+    pub fn error(&self) -> Option<String> {
+        match self {
+            Answer::Overflow(e) => Some(e.to_string()),
+            Answer::NoSolution(reason) => Some(reason.clone()),
+            _ => None,
+        }
+    }
+}
+
+impl From<i32> for Answer {
+    fn from(v: i32) -> Self {
+        Answer::Signed(v.into())
+    }
+}
+
+impl From<i64> for Answer {
+    fn from(v: i64) -> Self {
+        Answer::Signed(v)
+    }
+}
+
+impl From<u32> for Answer {
+    fn from(v: u32) -> Self {
+        Answer::Unsigned(v.into())
+    }
+}
+
+impl From<u64> for Answer {
+    fn from(v: u64) -> Self {
+        Answer::Unsigned(v.into())
+    }
+}
+
+impl From<u128> for Answer {
+    fn from(v: u128) -> Self {
+        Answer::Unsigned(v)
+    }
+}
+
+impl From<usize> for Answer {
+    fn from(v: usize) -> Self {
+        Answer::Unsigned(v as u128)
+    }
+}
+
+impl<T: Into<Answer>> From<Result<T, Overflow>> for Answer {
+    fn from(result: Result<T, Overflow>) -> Self {
+        match result {
+            Ok(v) => v.into(),
+            Err(e) => Answer::Overflow(e),
+        }
+    }
+}
+
+/// A day of the calendar, split into parsing and the two parts of the puzzle
+pub trait Solution {
+    /// The parsed puzzle input, shared by both parts
+    type Input;
+
+    fn parse(source: &InputSource) -> Result<Self::Input, ParseError>;
+    fn part1(input: &Self::Input) -> Answer;
+    fn part2(_input: &Self::Input) -> Answer {
+        Answer::Unsolved
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Part {
+    One,
+    Two,
+}
+
+impl Part {
+    /// Both parts, in order
+    pub const ALL: [Part; 2] = [Part::One, Part::Two];
+}
+
+impl fmt::Display for Part {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Part::One => write!(f, "1"),
+            Part::Two => write!(f, "2"),
+        }
+    }
+}
+
+/// The answer of one part and the time it took to solve
+#[derive(Debug, Clone)]
+pub struct PartReport {
+    pub part: Part,
+    pub answer: Answer,
+    pub duration: Duration,
+}
+
+/// Answers of one day, with the time spent parsing and solving each part
+#[derive(Debug, Clone)]
+pub struct Report {
+    pub day: u8,
+    pub parse_duration: Duration,
+    pub parts: Vec<PartReport>,
+}
+
+impl Report {
+    /// Time spent parsing and solving all parts
+    pub fn total_duration(&self) -> Duration {
+        self.parse_duration + self.parts.iter().map(|p| p.duration).sum::<Duration>()
+    }
+}
+
+/// Timing statistics of each benchmarked part, in the order the parts were requested
+pub type BenchResult = Result<Vec<(Part, Stats)>, ParseError>;
+
+/// Type erased entry in the registry, so days with different inputs can live in the same list
+pub struct Day {
+    pub number: u8,
+    pub title: &'static str,
+    solve: fn(u8, &InputSource, &[Part]) -> Result<Report, ParseError>,
+    bench: fn(&InputSource, &[Part], &BenchConfig) -> BenchResult,
+}
+
+impl Day {
+    const fn new<S: Solution>(number: u8, title: &'static str) -> Self {
+        Self {
+            number,
+            title,
+            solve: solve::<S>,
+            bench: bench::<S>,
+        }
+    }
+
+    /// Parses the input of this day and solves the requested parts, timing each step
+    pub fn solve(&self, source: &InputSource, parts: &[Part]) -> Result<Report, ParseError> {
+        (self.solve)(self.number, source, parts)
+    }
+
+    /// Parses the input of this day once and times the requested parts over many runs
+    pub fn bench(&self, source: &InputSource, parts: &[Part], config: &BenchConfig) -> BenchResult {
+        (self.bench)(source, parts, config)
+    }
+}
+
+fn solve_part<S: Solution>(part: Part, input: &S::Input) -> Answer {
+    match part {
+        Part::One => S::part1(input),
+        Part::Two => S::part2(input),
+    }
+}
+
+fn solve<S: Solution>(day: u8, source: &InputSource, parts: &[Part]) -> Result<Report, ParseError> {
+    let start = Instant::now();
+    let input = S::parse(source)?;
+    let parse_duration = start.elapsed();
+
+    let parts = parts
+        .iter()
+        .map(|part| {
+            let start = Instant::now();
+            let answer = solve_part::<S>(*part, &input);
+            PartReport {
+                part: *part,
+                answer,
+                duration: start.elapsed(),
+            }
+        })
+        .collect();
+
+    Ok(Report {
+        day,
+        parse_duration,
+        parts,
+    })
+}
+
+fn bench<S: Solution>(source: &InputSource, parts: &[Part], config: &BenchConfig) -> BenchResult {
+    let input = S::parse(source)?;
+    Ok(parts
+        .iter()
+        .map(|part| {
+            let stats = bench::measure(config, || {
+                black_box(solve_part::<S>(*part, black_box(&input)));
+            });
+            (*part, stats)
+        })
+        .collect())
+}
+
+/// All solved days, ordered by day number
+pub static DAYS: [Day; 9] = [
+    Day::new::<day1::Day1>(1, "Sonar Sweep"),
+    Day::new::<day2::Day2>(2, "Dive!"),
+    Day::new::<day3::Day3>(3, "Binary Diagnostic"),
+    Day::new::<day4::Day4>(4, "Giant Squid"),
+    Day::new::<day5::Day5>(5, "Hydrothermal Venture"),
+    Day::new::<day6::Day6>(6, "Lanternfish"),
+    Day::new::<day7::Day7>(7, "The Treachery of Whales"),
+    Day::new::<day8::Day8>(8, "Seven Segment Search"),
+    Day::new::<day9::Day9>(9, "Smoke Basin"),
+];
+
+/// The registered day with the given number
+pub fn find_day(number: u8) -> Option<&'static Day> {
+    DAYS.iter().find(|d| d.number == number)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_days_are_registered_in_order() {
+        let numbers = DAYS.iter().map(|d| d.number).collect::<Vec<u8>>();
+        assert_eq!(numbers, (1..=9).collect::<Vec<u8>>());
+    }
+
+    #[test]
+    fn test_find_day() {
+        assert_eq!(find_day(4).map(|d| d.title), Some("Giant Squid"));
+        assert!(find_day(0).is_none());
+        assert!(find_day(25).is_none());
+    }
+
+    #[test]
+    fn test_answer_from_integers() {
+        assert_eq!(Answer::from(-3_i32), Answer::Signed(-3));
+        assert_eq!(Answer::from(7_usize), Answer::Unsigned(7));
+        assert_eq!(Answer::from(12_u64).to_string(), "12");
+        assert_eq!(
+            Answer::from(u64::MAX as u128 * 2).to_string(),
+            "36893488147419103230"
+        );
+    }
+
+    #[test]
+    fn test_answer_from_result() {
+        assert_eq!(Answer::from(Ok::<u64, Overflow>(5)), Answer::Unsigned(5));
+        let answer = Answer::from(Err::<u64, _>(Overflow("fuel cost")));
+        assert_eq!(answer, Answer::Overflow(Overflow("fuel cost")));
+        assert_eq!(answer.to_string(), "overflow (fuel cost)");
+        assert_eq!(
+            answer.error(),
+            Some("fuel cost is too large to compute".to_string())
+        );
+        assert_eq!(Answer::Unsolved.error(), None);
+    }
+}
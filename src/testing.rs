@@ -0,0 +1,14 @@
+//! Helpers shared by the tests of several days.
+
+/// Pseudo-random numbers from a xorshift generator, the same on every run for one `seed`
+///
+/// A seed of 0 only ever gives 0.
+pub(crate) fn xorshift(seed: u64) -> impl Iterator<Item = u64> {
+    let mut state = seed;
+    std::iter::repeat_with(move || {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        state
+    })
+}
@@ -0,0 +1,161 @@
+//! Checking answers against the answers stored next to each input.
+
+use std::{
+    fs,
+    io::{self, BufRead, BufReader},
+};
+
+use crate::input::InputSource;
+use crate::parse::{numbered_lines, ParseError};
+use crate::solution::{Part, PartReport};
+
+/// Answers files hold one `<part>: <answer>` per line, lines starting with '#' are ignored
+#[derive(Debug, Default, PartialEq)]
+pub struct ExpectedAnswers {
+    part1: Option<String>,
+    part2: Option<String>,
+}
+
+impl ExpectedAnswers {
+    /// Expected answer of `part`, if known
+    pub fn get(&self, part: Part) -> Option<&str> {
+        match part {
+            Part::One => self.part1.as_deref(),
+            Part::Two => self.part2.as_deref(),
+        }
+    }
+}
+
+/// Outcome of comparing an answer with the expected one
+#[derive(Debug, PartialEq)]
+pub enum Verdict {
+    Pass,
+    Fail { expected: String },
+    Missing,
+}
+
+/// A day without an answers file has all its answers missing
+pub fn read_expected(source: &InputSource, day: u8) -> Result<ExpectedAnswers, ParseError> {
+    let path = match source.answers_path(day) {
+        Some(path) => path,
+        None => return Ok(ExpectedAnswers::default()),
+    };
+    match fs::File::open(&path) {
+        Ok(file) => parse_expected(BufReader::new(file)),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(ExpectedAnswers::default()),
+        Err(e) => Err(io::Error::new(e.kind(), format!("{}: {}", path.display(), e)).into()),
+    }
+}
+
+/// Parses the contents of an answers file
+pub fn parse_expected(reader: impl BufRead) -> Result<ExpectedAnswers, ParseError> {
+    let mut expected = ExpectedAnswers::default();
+    for line in numbered_lines(reader) {
+        let (line_number, line) = line?;
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        let (part, answer) = line.split_once(':').ok_or(ParseError::MissingToken {
+            line: line_number,
+            column: line.len() + 1,
+            expected: "':'",
+        })?;
+        let slot = match part.trim() {
+            "1" => &mut expected.part1,
+            "2" => &mut expected.part2,
+            p => {
+                return Err(ParseError::UnexpectedToken {
+                    line: line_number,
+                    column: line.find(p).unwrap_or(0) + 1,
+                    token: p.to_string(),
+                    expected: "part 1 or 2",
+                })
+            }
+        };
+        *slot = Some(answer.trim().to_string());
+    }
+    Ok(expected)
+}
+
+/// Compares the answer of one part with the expected answer
+pub fn check(expected: &ExpectedAnswers, report: &PartReport) -> Verdict {
+    match expected.get(report.part) {
+        Some(answer) if answer == report.answer.to_string() => Verdict::Pass,
+        Some(answer) => Verdict::Fail {
+            expected: answer.to_string(),
+        },
+        None => Verdict::Missing,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solution::Answer;
+    use std::time::Duration;
+
+    fn part_report(part: Part, answer: Answer) -> PartReport {
+        PartReport {
+            part,
+            answer,
+            duration: Duration::ZERO,
+        }
+    }
+
+    #[test]
+    fn test_parse_expected() {
+        let expected = parse_expected("# Day 7\n1: 37\n\n2:168\n".as_bytes()).unwrap();
+        assert_eq!(expected.get(Part::One), Some("37"));
+        assert_eq!(expected.get(Part::Two), Some("168"));
+
+        let expected = parse_expected("1: 37\n".as_bytes()).unwrap();
+        assert_eq!(expected.get(Part::Two), None);
+    }
+
+    #[test]
+    fn test_parse_expected_errors() {
+        assert_eq!(
+            parse_expected("1: 37\n3: 12\n".as_bytes())
+                .unwrap_err()
+                .to_string(),
+            "line 2, column 1: expected part 1 or 2, found '3'"
+        );
+        assert_eq!(
+            parse_expected("37\n".as_bytes()).unwrap_err().to_string(),
+            "line 1, column 3: expected ':'"
+        );
+    }
+
+    #[test]
+    fn test_check() {
+        let expected = parse_expected("1: 37\n".as_bytes()).unwrap();
+        assert_eq!(
+            check(&expected, &part_report(Part::One, Answer::from(37))),
+            Verdict::Pass
+        );
+        assert_eq!(
+            check(&expected, &part_report(Part::One, Answer::from(38))),
+            Verdict::Fail {
+                expected: "37".to_string()
+            }
+        );
+        assert_eq!(
+            check(&expected, &part_report(Part::Two, Answer::from(168))),
+            Verdict::Missing
+        );
+    }
+
+    #[test]
+    fn test_missing_answers_file() {
+        let source = InputSource::Dir("does/not/exist".into());
+        assert_eq!(
+            read_expected(&source, 1).unwrap(),
+            ExpectedAnswers::default()
+        );
+        assert_eq!(
+            read_expected(&InputSource::Stdin, 1).unwrap(),
+            ExpectedAnswers::default()
+        );
+    }
+}
@@ -0,0 +1,24 @@
+use aoc2021::input::InputSource;
+use aoc2021::solution::{Part, DAYS};
+use aoc2021::verify::{self, Verdict};
+
+// The checked-in inputs must keep producing the checked-in answers
+#[test]
+fn test_checked_in_answers() {
+    let source = InputSource::default();
+    for day in DAYS.iter() {
+        let expected = verify::read_expected(&source, day.number).unwrap();
+        let report = day.solve(&source, &Part::ALL).unwrap();
+        for part in report.parts.iter() {
+            let verdict = verify::check(&expected, part);
+            assert!(
+                !matches!(verdict, Verdict::Fail { .. }),
+                "day {} part {}: {:?}, got {}",
+                day.number,
+                part.part,
+                verdict,
+                part.answer
+            );
+        }
+    }
+}